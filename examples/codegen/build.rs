@@ -0,0 +1,8 @@
+use std::env;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=dictionary");
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("radius.rs");
+    radius_client::codegen::generate("dictionary", out).unwrap();
+}
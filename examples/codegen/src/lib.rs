@@ -0,0 +1,109 @@
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+//! attribute constants, enums and accessors generated by `build.rs` from `dictionary`
+//!
+//! misspelled attributes don't compile:
+//!
+//! ```compile_fail
+//! let _ = radius_codegen_example::attributes::USER_NAEM;
+//! ```
+//!
+//! ```compile_fail
+//! use radius_codegen_example::attributes::DictionaryAttributes;
+//!
+//! let data = radius_client::radius::RadiusData::new(radius_client::radius::RadiusCode::AccessRequest, None, None, None);
+//! let _ = data.get_user_naem();
+//! ```
+//!
+//! neither do wrongly typed values:
+//!
+//! ```compile_fail
+//! use radius_codegen_example::attributes::DictionaryAttributes;
+//!
+//! let dict = radius_client::client::dictionary::Dictionary::default();
+//! let mut data = radius_client::radius::RadiusData::new(radius_client::radius::RadiusCode::AccessRequest, None, None, None);
+//! data.set_service_type(&dict, 2).unwrap();
+//! ```
+//!
+//! ```compile_fail
+//! use radius_codegen_example::attributes::DictionaryAttributes;
+//!
+//! let dict = radius_client::client::dictionary::Dictionary::default();
+//! let mut data = radius_client::radius::RadiusData::new(radius_client::radius::RadiusCode::AccessRequest, None, None, None);
+//! data.set_test_byte(&dict, 256).unwrap();
+//! ```
+//!
+//! while the right ones do:
+//!
+//! ```
+//! use radius_codegen_example::attributes::{DictionaryAttributes, ServiceType};
+//!
+//! let dict = radius_client::client::dictionary::Dictionary::default();
+//! let mut data = radius_client::radius::RadiusData::new(radius_client::radius::RadiusCode::AccessRequest, None, None, None);
+//! data.set_service_type(&dict, ServiceType::FramedUser).unwrap();
+//! assert_eq!(data.get_service_type(), Some(ServiceType::FramedUser));
+//! ```
+
+/// generated code
+pub mod attributes {
+    include!(concat!(env!("OUT_DIR"), "/radius.rs"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::attributes::*;
+    use radius_client::client::dictionary::Dictionary;
+    use radius_client::radius::{RadiusCode, RadiusData};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn accessors() {
+        let dict = Dictionary::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/dictionary")).unwrap();
+        let mut data = RadiusData::new(RadiusCode::AccessRequest, None, None, None);
+
+        assert_eq!(USER_NAME, "User-Name");
+        assert_eq!(_3GPP_IMSI, "3GPP-IMSI");
+        assert_eq!(data.get_user_name(), None);
+
+        data.set_user_name(&dict, String::from("user")).unwrap();
+        data.set_user_name(&dict, String::from("other")).unwrap();
+        data.set_class(&dict, b"class".to_vec()).unwrap();
+        data.set_service_type(&dict, ServiceType::Framed).unwrap();
+        data.set_framed_ip_address(&dict, Ipv4Addr::new(10, 0, 0, 1)).unwrap();
+        data.set_event_timestamp(&dict, 1_600_000_000).unwrap();
+        data.set_framed_ipv6_address(&dict, Ipv6Addr::LOCALHOST).unwrap();
+        data.set_test_short(&dict, TestShort::Max).unwrap();
+        data.set_test_byte(&dict, TestByte::Max).unwrap();
+        data.set_test_signed(&dict, -1).unwrap();
+        data.set_test_integer64(&dict, u64::MAX).unwrap();
+        data.set_3gpp_imsi(&dict, String::from("001010123456789")).unwrap();
+        data.set_3gpp_pdp_type(&dict, _3gppPdpType::Ipv6).unwrap();
+        // nested attributes are replaced too, in the tlv created by the first one
+        data.set_test_tlv_name(&dict, String::from("first")).unwrap();
+        data.set_test_tlv_port(&dict, 1812).unwrap();
+        data.set_test_tlv_name(&dict, String::from("second")).unwrap();
+
+        let data = dict.load_bytes(&data.get_bytes().unwrap()).unwrap();
+        assert_eq!(data.get_attributes_by_name(USER_NAME).count(), 1);
+        assert_eq!(data.get_user_name(), Some(String::from("other")));
+        assert_eq!(data.get_class(), Some(b"class".to_vec()));
+        // Framed and Framed-User share the number, the first one wins
+        assert_eq!(data.get_service_type(), Some(ServiceType::FramedUser));
+        assert_eq!(data.get_framed_ip_address(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(data.get_event_timestamp(), Some(1_600_000_000));
+        assert_eq!(data.get_framed_ipv6_address(), Some(Ipv6Addr::LOCALHOST));
+        assert_eq!(data.get_test_short(), Some(TestShort::Max));
+        assert_eq!(TestShort::Self_.to_u32(), 1);
+        assert_eq!(data.get_test_byte(), Some(TestByte::Max));
+        assert_eq!(data.get_test_signed(), Some(-1));
+        assert_eq!(data.get_test_integer64(), Some(u64::MAX));
+        assert_eq!(data.get_3gpp_imsi(), Some(String::from("001010123456789")));
+        assert_eq!(data.get_3gpp_pdp_type(), Some(_3gppPdpType::Ipv6));
+        let tlv: Vec<_> = data.get_attributes_by_name("Test-Tlv").collect();
+        assert_eq!(tlv.len(), 1);
+        assert_eq!(tlv[0].get_children().len(), 2);
+        assert_eq!(data.get_test_tlv_name(), Some(String::from("second")));
+        assert_eq!(data.get_test_tlv_port(), Some(1812));
+    }
+}
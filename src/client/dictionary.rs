@@ -1,12 +1,21 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use super::{RadiusCode, RadiusAttribute, RadiusAttributeDef, RadiusData};
+#[cfg(feature = "serde")]
+use super::{DataEntry, EntryValue, from_hex};
+use super::{RadiusCode, RadiusAttribute, RadiusAttributeDef, RadiusAttributeType, RadiusVendorDef, RadiusValueDef, RadiusData, IntoRadiusValue, MAX_PACKET_LENGTH, VENDOR_SPECIFIC, LONG_EXTENDED_MORE};
 
 /// Default Dictionary
+/// attributes of the IANA radius-types registry, as loaded by `Dictionary::from_iana_csv`,
+/// with FreeRADIUS spelling and flags
 #[allow(dead_code)]
 pub const DEFAULT_DICTIONARY: &str = r"
 ATTRIBUTE   User-Name       1   string
-ATTRIBUTE   User-Password       2   string
+ATTRIBUTE   User-Password       2   octets  encrypt=1
 ATTRIBUTE   CHAP-Password       3   octets
 ATTRIBUTE   NAS-IP-Address      4   ipaddr
 ATTRIBUTE   NAS-Port        5   integer
@@ -28,7 +37,7 @@ ATTRIBUTE   Framed-Route        22  string
 ATTRIBUTE   Framed-IPX-Network  23  ipaddr
 ATTRIBUTE   State           24  octets
 ATTRIBUTE   Class           25  octets
-ATTRIBUTE   Vendor-Specific     26  octets
+ATTRIBUTE   Vendor-Specific     26  vsa
 ATTRIBUTE   Session-Timeout     27  integer
 ATTRIBUTE   Idle-Timeout        28  integer
 ATTRIBUTE   Termination-Action  29  integer
@@ -58,16 +67,25 @@ ATTRIBUTE    Acct-Link-Count        51    integer
 ATTRIBUTE    Acct-Input-Gigawords   52    integer
 ATTRIBUTE    Acct-Output-Gigawords  53    integer
 ATTRIBUTE    Event-Timestamp        55    date
+ATTRIBUTE    Egress-VLANID          56    integer
+ATTRIBUTE    Ingress-Filters        57    integer
+ATTRIBUTE    Egress-VLAN-Name       58    string
+ATTRIBUTE    User-Priority-Table    59    octets
 
-ATTRIBUTE    CHAP-Challenge          60   string
+ATTRIBUTE    CHAP-Challenge          60   octets
 ATTRIBUTE    NAS-Port-Type           61   integer
 ATTRIBUTE    Port-Limit              62   integer
-ATTRIBUTE    Login-LAT-Port          63   integer
+ATTRIBUTE    Login-LAT-Port          63   string
+ATTRIBUTE    Tunnel-Type             64   integer has_tag
+ATTRIBUTE    Tunnel-Medium-Type      65   integer has_tag
+ATTRIBUTE    Tunnel-Client-Endpoint  66   string  has_tag
+ATTRIBUTE    Tunnel-Server-Endpoint  67   string  has_tag
 
 ATTRIBUTE    Acct-Tunnel-Connection  68   string
+ATTRIBUTE    Tunnel-Password         69   octets  has_tag,encrypt=2
 
-ATTRIBUTE    ARAP-Password           70   string
-ATTRIBUTE    ARAP-Features           71   string
+ATTRIBUTE    ARAP-Password           70   octets
+ATTRIBUTE    ARAP-Features           71   octets
 ATTRIBUTE    ARAP-Zone-Access        72   integer
 ATTRIBUTE    ARAP-Security           73   integer
 ATTRIBUTE    ARAP-Security-Data      74   string
@@ -75,88 +93,1736 @@ ATTRIBUTE    Password-Retry          75   integer
 ATTRIBUTE    Prompt                  76   integer
 ATTRIBUTE    Connect-Info            77   string
 ATTRIBUTE    Configuration-Token     78   string
-ATTRIBUTE    EAP-Message             79   string
+ATTRIBUTE    EAP-Message             79   octets  concat
 ATTRIBUTE    Message-Authenticator   80   octets
-ATTRIBUTE    ARAP-Challenge-Response 84   string
+ATTRIBUTE    Tunnel-Private-Group-Id 81   string  has_tag
+ATTRIBUTE    Tunnel-Assignment-Id    82   string  has_tag
+ATTRIBUTE    Tunnel-Preference       83   integer has_tag
+ATTRIBUTE    ARAP-Challenge-Response 84   octets
 ATTRIBUTE    Acct-Interim-Interval   85   integer
+ATTRIBUTE    Acct-Tunnel-Packets-Lost 86  integer
 ATTRIBUTE    NAS-Port-Id             87   string
 ATTRIBUTE    Framed-Pool             88   string
-ATTRIBUTE    NAS-IPv6-Address        95   octets
-ATTRIBUTE    Framed-Interface-Id     96   octets
-ATTRIBUTE    Framed-IPv6-Prefix      97   octets
-ATTRIBUTE    Login-IPv6-Host         98   octets
+ATTRIBUTE    Chargeable-User-Identity 89  octets
+ATTRIBUTE    Tunnel-Client-Auth-Id   90   string  has_tag
+ATTRIBUTE    Tunnel-Server-Auth-Id   91   string  has_tag
+ATTRIBUTE    NAS-Filter-Rule         92   string
+ATTRIBUTE    Originating-Line-Info   94   octets
+ATTRIBUTE    NAS-IPv6-Address        95   ipv6addr
+ATTRIBUTE    Framed-Interface-Id     96   ifid
+ATTRIBUTE    Framed-IPv6-Prefix      97   ipv6prefix
+ATTRIBUTE    Login-IPv6-Host         98   ipv6addr
 ATTRIBUTE    Framed-IPv6-Route       99   string
 ATTRIBUTE    Framed-IPv6-Pool        100  string
+ATTRIBUTE    Error-Cause             101  integer
+ATTRIBUTE    EAP-Key-Name            102  octets
+# 103 Digest-Response (RFC 5090) clashes with the draft Digest-Response (206) kept below
+ATTRIBUTE    Digest-Realm            104  string
+ATTRIBUTE    Digest-Nonce            105  string
+ATTRIBUTE    Digest-Response-Auth    106  string
+ATTRIBUTE    Digest-Nextnonce        107  string
+ATTRIBUTE    Digest-Method           108  string
+ATTRIBUTE    Digest-URI              109  string
+ATTRIBUTE    Digest-Qop              110  string
+ATTRIBUTE    Digest-Algorithm        111  string
+ATTRIBUTE    Digest-Entity-Body-Hash 112  string
+ATTRIBUTE    Digest-CNonce           113  string
+ATTRIBUTE    Digest-Nonce-Count      114  string
+ATTRIBUTE    Digest-Username         115  string
+ATTRIBUTE    Digest-Opaque           116  string
+ATTRIBUTE    Digest-Auth-Param       117  string
+ATTRIBUTE    Digest-AKA-Auts         118  string
+ATTRIBUTE    Digest-Domain           119  string
+ATTRIBUTE    Digest-Stale            120  string
+ATTRIBUTE    Digest-HA1              121  string
+ATTRIBUTE    SIP-AOR                 122  string
+ATTRIBUTE    Delegated-IPv6-Prefix   123  ipv6prefix
+ATTRIBUTE    MIP6-Feature-Vector     124  integer64
+ATTRIBUTE    MIP6-Home-Link-Prefix   125  ipv6prefix
+ATTRIBUTE    Operator-Name           126  string
+ATTRIBUTE    Location-Information    127  octets
+ATTRIBUTE    Location-Data           128  octets
+ATTRIBUTE    Basic-Location-Policy-Rules 129  octets
+ATTRIBUTE    Extended-Location-Policy-Rules 130  octets
+ATTRIBUTE    Location-Capable        131  integer
+ATTRIBUTE    Requested-Location-Info 132  integer
+ATTRIBUTE    Framed-Management-Protocol 133  integer
+ATTRIBUTE    Management-Transport-Protection 134  integer
+ATTRIBUTE    Management-Policy-Id    135  string
+ATTRIBUTE    Management-Privilege-Level 136  integer
+ATTRIBUTE    PKM-SS-Cert             137  octets  concat
+ATTRIBUTE    PKM-CA-Cert             138  octets  concat
+ATTRIBUTE    PKM-Config-Settings     139  octets
+ATTRIBUTE    PKM-Cryptosuite-List    140  octets
+ATTRIBUTE    PKM-SAID                141  short
+ATTRIBUTE    PKM-SA-Descriptor       142  octets
+ATTRIBUTE    PKM-Auth-Key            143  octets
+ATTRIBUTE    DS-Lite-Tunnel-Name     144  string
+ATTRIBUTE    Mobile-Node-Identifier  145  octets
+ATTRIBUTE    Service-Selection       146  string
+ATTRIBUTE    PMIP6-Home-LMA-IPv6-Address 147  ipv6addr
+ATTRIBUTE    PMIP6-Visited-LMA-IPv6-Address 148  ipv6addr
+ATTRIBUTE    PMIP6-Home-LMA-IPv4-Address 149  ipaddr
+ATTRIBUTE    PMIP6-Visited-LMA-IPv4-Address 150  ipaddr
+ATTRIBUTE    PMIP6-Home-HN-Prefix    151  ipv6prefix
+ATTRIBUTE    PMIP6-Visited-HN-Prefix 152  ipv6prefix
+ATTRIBUTE    PMIP6-Home-Interface-ID 153  ifid
+ATTRIBUTE    PMIP6-Visited-Interface-ID 154  ifid
+ATTRIBUTE    PMIP6-Home-IPv4-HoA     155  ipv4prefix
+ATTRIBUTE    PMIP6-Visited-IPv4-HoA  156  ipv4prefix
+ATTRIBUTE    PMIP6-Home-DHCP4-Server-Address 157  ipaddr
+ATTRIBUTE    PMIP6-Visited-DHCP4-Server-Address 158  ipaddr
+ATTRIBUTE    PMIP6-Home-DHCP6-Server-Address 159  ipv6addr
+ATTRIBUTE    PMIP6-Visited-DHCP6-Server-Address 160  ipv6addr
+ATTRIBUTE    PMIP6-Home-IPv4-Gateway 161  ipaddr
+ATTRIBUTE    PMIP6-Visited-IPv4-Gateway 162  ipaddr
+ATTRIBUTE    EAP-Lower-Layer         163  integer
+ATTRIBUTE    GSS-Acceptor-Service-Name 164  string
+ATTRIBUTE    GSS-Acceptor-Host-Name  165  string
+ATTRIBUTE    GSS-Acceptor-Service-Specifics 166  string
+ATTRIBUTE    GSS-Acceptor-Realm-Name 167  string
+ATTRIBUTE    Framed-IPv6-Address     168  ipv6addr
+ATTRIBUTE    DNS-Server-IPv6-Address 169  ipv6addr
+ATTRIBUTE    Route-IPv6-Information  170  ipv6prefix
+ATTRIBUTE    Delegated-IPv6-Prefix-Pool 171  string
+ATTRIBUTE    Stateful-IPv6-Address-Pool 172  string
+ATTRIBUTE    IPv6-6rd-Configuration  173  tlv
+ATTRIBUTE    IPv6-6rd-IPv4MaskLen    173.1  integer
+ATTRIBUTE    IPv6-6rd-Prefix         173.2  ipv6prefix
+ATTRIBUTE    IPv6-6rd-BR-IPv4-Address 173.3  ipaddr
+ATTRIBUTE    Allowed-Called-Station-Id 174  string
+ATTRIBUTE    EAP-Peer-Id             175  octets
+ATTRIBUTE    EAP-Server-Id           176  octets
+ATTRIBUTE    Mobility-Domain-Id      177  integer
+ATTRIBUTE    Preauth-Timeout         178  integer
+ATTRIBUTE    Network-Id-Name         179  octets
+ATTRIBUTE    EAPoL-Announcement      180  octets  concat
+ATTRIBUTE    WLAN-HESSID             181  string
+ATTRIBUTE    WLAN-Venue-Info         182  integer
+ATTRIBUTE    WLAN-Venue-Language     183  octets
+ATTRIBUTE    WLAN-Venue-Name         184  string
+ATTRIBUTE    WLAN-Reason-Code        185  integer
+ATTRIBUTE    WLAN-Pairwise-Cipher    186  integer
+ATTRIBUTE    WLAN-Group-Cipher       187  integer
+ATTRIBUTE    WLAN-AKM-Suite          188  integer
+ATTRIBUTE    WLAN-Group-Mgmt-Cipher  189  integer
+ATTRIBUTE    WLAN-RF-Band            190  integer
 
 ATTRIBUTE    Digest-Response        206   string
 ATTRIBUTE    Digest-Attributes      207   octets
+
+ATTRIBUTE    Extended-Attribute-1   241   extended
+ATTRIBUTE    Frag-Status            241.1 integer
+ATTRIBUTE    Proxy-State-Length     241.2 integer
+ATTRIBUTE    Response-Length        241.3 integer
+ATTRIBUTE    Original-Packet-Code   241.4 integer
+ATTRIBUTE    IP-Port-Limit-Info     241.5 tlv
+ATTRIBUTE    IP-Port-Range          241.6 tlv
+ATTRIBUTE    IP-Port-Forwarding-Map 241.7 tlv
+ATTRIBUTE    Operator-NAS-Identifier 241.8 octets
+ATTRIBUTE    Softwire46-Configuration 241.9 tlv
+ATTRIBUTE    Softwire46-Priority    241.10 tlv
+ATTRIBUTE    Softwire46-Multicast   241.11 tlv
+ATTRIBUTE    Extended-Vendor-Specific-1 241.26 evs
+ATTRIBUTE    Extended-Attribute-2   242   extended
+ATTRIBUTE    Extended-Vendor-Specific-2 242.26 evs
+ATTRIBUTE    Extended-Attribute-3   243   extended
+ATTRIBUTE    Extended-Vendor-Specific-3 243.26 evs
+ATTRIBUTE    Extended-Attribute-4   244   extended
+ATTRIBUTE    Extended-Vendor-Specific-4 244.26 evs
+ATTRIBUTE    Extended-Attribute-5   245   long-extended
+ATTRIBUTE    Extended-Vendor-Specific-5 245.26 evs
+ATTRIBUTE    Extended-Attribute-6   246   long-extended
+ATTRIBUTE    Extended-Vendor-Specific-6 246.26 evs
+
+VALUE    Service-Type           Login-User              1
+VALUE    Service-Type           Framed-User             2
+VALUE    Service-Type           Callback-Login-User     3
+VALUE    Service-Type           Callback-Framed-User    4
+VALUE    Service-Type           Outbound-User           5
+VALUE    Service-Type           Administrative-User     6
+VALUE    Service-Type           NAS-Prompt-User         7
+VALUE    Service-Type           Authenticate-Only       8
+VALUE    Service-Type           Callback-NAS-Prompt     9
+VALUE    Service-Type           Call-Check              10
+VALUE    Service-Type           Callback-Administrative 11
+
+VALUE    Framed-Protocol        PPP                     1
+VALUE    Framed-Protocol        SLIP                    2
+VALUE    Framed-Protocol        ARAP                    3
+VALUE    Framed-Protocol        Gandalf-SLML            4
+VALUE    Framed-Protocol        Xylogics-IPX-SLIP       5
+VALUE    Framed-Protocol        X.75-Synchronous        6
+
+VALUE    Framed-Routing         None                    0
+VALUE    Framed-Routing         Broadcast               1
+VALUE    Framed-Routing         Listen                  2
+VALUE    Framed-Routing         Broadcast-Listen        3
+
+VALUE    Framed-Compression     None                    0
+VALUE    Framed-Compression     Van-Jacobson-TCP-IP     1
+VALUE    Framed-Compression     IPX-Header-Compression  2
+VALUE    Framed-Compression     Stac-LZS                3
+
+VALUE    Login-Service          Telnet                  0
+VALUE    Login-Service          Rlogin                  1
+VALUE    Login-Service          TCP-Clear               2
+VALUE    Login-Service          PortMaster              3
+VALUE    Login-Service          LAT                     4
+VALUE    Login-Service          X25-PAD                 5
+VALUE    Login-Service          X25-T3POS               6
+VALUE    Login-Service          TCP-Clear-Quiet         8
+
+VALUE    Termination-Action     Default                 0
+VALUE    Termination-Action     RADIUS-Request          1
+
+VALUE    Acct-Status-Type       Start                   1
+VALUE    Acct-Status-Type       Stop                    2
+VALUE    Acct-Status-Type       Interim-Update          3
+VALUE    Acct-Status-Type       Accounting-On           7
+VALUE    Acct-Status-Type       Accounting-Off          8
+VALUE    Acct-Status-Type       Failed                  15
+
+VALUE    Acct-Authentic         RADIUS                  1
+VALUE    Acct-Authentic         Local                   2
+VALUE    Acct-Authentic         Remote                  3
+VALUE    Acct-Authentic         Diameter                4
+
+VALUE    Acct-Terminate-Cause   User-Request            1
+VALUE    Acct-Terminate-Cause   Lost-Carrier            2
+VALUE    Acct-Terminate-Cause   Lost-Service            3
+VALUE    Acct-Terminate-Cause   Idle-Timeout            4
+VALUE    Acct-Terminate-Cause   Session-Timeout         5
+VALUE    Acct-Terminate-Cause   Admin-Reset             6
+VALUE    Acct-Terminate-Cause   Admin-Reboot            7
+VALUE    Acct-Terminate-Cause   Port-Error              8
+VALUE    Acct-Terminate-Cause   NAS-Error               9
+VALUE    Acct-Terminate-Cause   NAS-Request             10
+VALUE    Acct-Terminate-Cause   NAS-Reboot              11
+VALUE    Acct-Terminate-Cause   Port-Unneeded           12
+VALUE    Acct-Terminate-Cause   Port-Preempted          13
+VALUE    Acct-Terminate-Cause   Port-Suspended          14
+VALUE    Acct-Terminate-Cause   Service-Unavailable     15
+VALUE    Acct-Terminate-Cause   Callback                16
+VALUE    Acct-Terminate-Cause   User-Error              17
+VALUE    Acct-Terminate-Cause   Host-Request            18
+
+VALUE    NAS-Port-Type          Async                   0
+VALUE    NAS-Port-Type          Sync                    1
+VALUE    NAS-Port-Type          ISDN                    2
+VALUE    NAS-Port-Type          ISDN-V120               3
+VALUE    NAS-Port-Type          ISDN-V110               4
+VALUE    NAS-Port-Type          Virtual                 5
+VALUE    NAS-Port-Type          PIAFS                   6
+VALUE    NAS-Port-Type          HDLC-Clear-Channel      7
+VALUE    NAS-Port-Type          X.25                    8
+VALUE    NAS-Port-Type          X.75                    9
+VALUE    NAS-Port-Type          G.3-Fax                 10
+VALUE    NAS-Port-Type          SDSL                    11
+VALUE    NAS-Port-Type          ADSL-CAP                12
+VALUE    NAS-Port-Type          ADSL-DMT                13
+VALUE    NAS-Port-Type          IDSL                    14
+VALUE    NAS-Port-Type          Ethernet                15
+VALUE    NAS-Port-Type          xDSL                    16
+VALUE    NAS-Port-Type          Cable                   17
+VALUE    NAS-Port-Type          Wireless-Other          18
+VALUE    NAS-Port-Type          Wireless-802.11         19
+
+VALUE    Frag-Status            Reserved                0
+VALUE    Frag-Status            Fragmentation-Supported 1
+VALUE    Frag-Status            More-Data-Pending       2
+VALUE    Frag-Status            More-Data-Request       3
+
+VALUE    Prompt                 No-Echo                 0
+VALUE    Prompt                 Echo                    1
+
+VALUE    Tunnel-Type            PPTP                    1
+VALUE    Tunnel-Type            L2F                     2
+VALUE    Tunnel-Type            L2TP                    3
+VALUE    Tunnel-Type            ATMP                    4
+VALUE    Tunnel-Type            VTP                     5
+VALUE    Tunnel-Type            AH                      6
+VALUE    Tunnel-Type            IP                      7
+VALUE    Tunnel-Type            MIN-IP                  8
+VALUE    Tunnel-Type            ESP                     9
+VALUE    Tunnel-Type            GRE                     10
+VALUE    Tunnel-Type            DVS                     11
+VALUE    Tunnel-Type            IP-in-IP                12
+VALUE    Tunnel-Type            VLAN                    13
+
+VALUE    Tunnel-Medium-Type     IPv4                    1
+VALUE    Tunnel-Medium-Type     IPv6                    2
+VALUE    Tunnel-Medium-Type     NSAP                    3
+VALUE    Tunnel-Medium-Type     HDLC                    4
+VALUE    Tunnel-Medium-Type     BBN-1822                5
+VALUE    Tunnel-Medium-Type     IEEE-802                6
+VALUE    Tunnel-Medium-Type     E.163                   7
+VALUE    Tunnel-Medium-Type     E.164                   8
+VALUE    Tunnel-Medium-Type     F.69                    9
+VALUE    Tunnel-Medium-Type     X.121                   10
+VALUE    Tunnel-Medium-Type     IPX                     11
+VALUE    Tunnel-Medium-Type     Appletalk               12
+VALUE    Tunnel-Medium-Type     DecNet-IV               13
+VALUE    Tunnel-Medium-Type     Banyan-Vines            14
+VALUE    Tunnel-Medium-Type     E.164-NSAP              15
+
+VALUE    Error-Cause            Residual-Context-Removed 201
+VALUE    Error-Cause            Invalid-EAP-Packet      202
+VALUE    Error-Cause            Unsupported-Attribute   401
+VALUE    Error-Cause            Missing-Attribute       402
+VALUE    Error-Cause            NAS-Identification-Mismatch 403
+VALUE    Error-Cause            Invalid-Request         404
+VALUE    Error-Cause            Unsupported-Service     405
+VALUE    Error-Cause            Unsupported-Extension   406
+VALUE    Error-Cause            Invalid-Attribute-Value 407
+VALUE    Error-Cause            Administratively-Prohibited 501
+VALUE    Error-Cause            Request-Not-Routable    502
+VALUE    Error-Cause            Session-Context-Not-Found 503
+VALUE    Error-Cause            Session-Context-Not-Removable 504
+VALUE    Error-Cause            Other-Proxy-Processing-Error 505
+VALUE    Error-Cause            Resources-Unavailable   506
+VALUE    Error-Cause            Request-Initiated       507
+VALUE    Error-Cause            Multiple-Session-Selection-Unsupported 508
 ";
 
-/// Dictionary struct
-/// Actually manager only attributes rows, limited to 255
-pub struct Dictionary {
-    attributes: Vec<RadiusAttributeDef>
-}
+/// Conflict resolution between the definitions of merged dictionaries
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Precedence {
+    /// definitions already present win, conflicting incoming ones are skipped
+    Existing,
+    /// incoming definitions replace the conflicting ones already present
+    Incoming,
+    /// conflicting definitions are an error, identical ones are merged
+    Strict,
+}
+
+/// Bundled vendor dictionaries, each one enabled by its own cargo feature
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Vendor {
+    /// 3GPP (10415), feature `vendor-3gpp`
+    #[cfg(feature = "vendor-3gpp")]
+    ThreeGpp,
+    /// 3GPP2 (5535), feature `vendor-3gpp2`
+    #[cfg(feature = "vendor-3gpp2")]
+    ThreeGpp2,
+    /// Microsoft (311), feature `vendor-microsoft`
+    #[cfg(feature = "vendor-microsoft")]
+    Microsoft,
+    /// Cisco (9), feature `vendor-cisco`
+    #[cfg(feature = "vendor-cisco")]
+    Cisco,
+    /// Juniper (2636), feature `vendor-juniper`
+    #[cfg(feature = "vendor-juniper")]
+    Juniper,
+    /// WiMAX Forum (24757), feature `vendor-wimax`
+    #[cfg(feature = "vendor-wimax")]
+    WiMax,
+    /// Broadband Forum, formerly DSL Forum (3561), feature `vendor-dsl-forum`
+    #[cfg(feature = "vendor-dsl-forum")]
+    DslForum,
+}
+
+impl Vendor {
+    /// retrieve vendor dictionary text
+    pub fn get_dictionary(self) -> &'static str {
+        match self {
+            #[cfg(feature = "vendor-3gpp")]
+            Vendor::ThreeGpp => include_str!("dictionaries/dictionary.3gpp"),
+            #[cfg(feature = "vendor-3gpp2")]
+            Vendor::ThreeGpp2 => include_str!("dictionaries/dictionary.3gpp2"),
+            #[cfg(feature = "vendor-microsoft")]
+            Vendor::Microsoft => include_str!("dictionaries/dictionary.microsoft"),
+            #[cfg(feature = "vendor-cisco")]
+            Vendor::Cisco => include_str!("dictionaries/dictionary.cisco"),
+            #[cfg(feature = "vendor-juniper")]
+            Vendor::Juniper => include_str!("dictionaries/dictionary.juniper"),
+            #[cfg(feature = "vendor-wimax")]
+            Vendor::WiMax => include_str!("dictionaries/dictionary.wimax"),
+            #[cfg(feature = "vendor-dsl-forum")]
+            Vendor::DslForum => include_str!("dictionaries/dictionary.dslforum"),
+        }
+    }
+}
+
+// vendor of a BEGIN-VENDOR block, with its Extended-Vendor-Specific attribute if any
+type VendorBlock = (RadiusVendorDef, Option<RadiusAttributeDef>);
+
+// attribute position inside its space: parent oid, vendor id and code
+type AttributeKey = (Option<String>, Option<u32>, u8);
+
+// row error, with the index of the offending field
+type RowError = (usize, String);
+
+/// Dictionary loading error, located by source file, line and column
+#[derive(Clone, PartialEq, Debug)]
+pub struct DictionaryError {
+    source: Option<PathBuf>,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl DictionaryError {
+    fn new(source: Option<&Path>, line: usize, column: usize, message: String) -> DictionaryError {
+        DictionaryError {
+            source: source.map(|s| s.to_path_buf()),
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// retrieve the file the error comes from, None for dictionary text
+    pub fn get_source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// retrieve the 1-based line, 0 if the error is not bound to a row (e.g. unreadable file)
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    /// retrieve the 1-based column, 0 if the error is not bound to a row
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    /// retrieve the error message
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.source, self.line) {
+            (Some(source), 0) => write!(f, "{}: {}", source.display(), self.message),
+            (Some(source), line) => write!(f, "{}:{}:{}: {}", source.display(), line, self.column, self.message),
+            (None, 0) => write!(f, "{}", self.message),
+            (None, line) => write!(f, "line {} column {}: {}", line, self.column, self.message),
+        }
+    }
+}
+
+impl Error for DictionaryError {}
+
+/// Packet decoding error, located by byte offset in the datagram
+#[derive(Clone, PartialEq, Debug)]
+pub struct PacketError {
+    offset: usize,
+    message: String,
+}
+
+impl PacketError {
+    fn new(offset: usize, message: String) -> PacketError {
+        PacketError {
+            offset,
+            message,
+        }
+    }
+
+    /// retrieve the offset of the faulty field, or of the attribute holding it
+    pub fn get_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// retrieve the error message
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "offset {}: {}", self.offset, self.message)
+    }
+}
+
+impl Error for PacketError {}
+
+// state of a dictionary loading: include stack and, in strict mode, the problems found so far
+struct ParseContext {
+    stack: Vec<PathBuf>,
+    strict: bool,
+    errors: Vec<DictionaryError>,
+}
+
+impl ParseContext {
+    fn new(strict: bool) -> ParseContext {
+        ParseContext {
+            stack: Vec::new(),
+            strict,
+            errors: Vec::new(),
+        }
+    }
+
+    // lenient loading stops at the first problem, strict one collects them all
+    fn report(&mut self, error: DictionaryError) -> Result<(), DictionaryError> {
+        if !self.strict {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+}
+
+// CSV records, each field with its line and column, quoted fields may span multiple lines
+fn csv_records(text: &str) -> Vec<Vec<(usize, usize, String)>> {
+    let mut res = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let (mut line, mut column) = (1, 1);
+    let mut start = (1, 1);
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                column += 1;
+                field.push('"');
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                record.push((start.0, start.1, std::mem::take(&mut field)));
+                start = (line, column + 1);
+            },
+            '\n' if !quoted => {
+                record.push((start.0, start.1, std::mem::take(&mut field)));
+                res.push(std::mem::take(&mut record));
+                line += 1;
+                column = 0;
+                start = (line, 1);
+            },
+            '\r' if !quoted => {},
+            c => {
+                if c == '\n' {
+                    line += 1;
+                    column = 0;
+                }
+                field.push(c);
+            },
+        }
+        column += 1;
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push((start.0, start.1, field));
+        res.push(record);
+    }
+    res.retain(|r| r.iter().any(|(_, _, f)| !f.is_empty()));
+    res
+}
+
+// quoted and escaped JSON string
+fn json_string(s: &str) -> String {
+    let mut res = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+// 1-based column of a whitespace separated field of a row
+fn column(row: &str, field: usize) -> usize {
+    let mut n = 0;
+    let mut separator = true;
+    for (i, c) in row.char_indices() {
+        if separator && !c.is_whitespace() {
+            if n == field {
+                return i + 1;
+            }
+            n += 1;
+        }
+        separator = c.is_whitespace();
+    }
+    1
+}
+
+/// Dictionary struct
+/// Actually manages attributes, vendors and values rows, limited to 255 codes per vendor
+#[derive(Clone)]
+pub struct Dictionary {
+    attributes: Vec<RadiusAttributeDef>,
+    vendors: Vec<RadiusVendorDef>,
+    // named values, grouped by attribute name
+    values: HashMap<String, Vec<RadiusValueDef>>,
+    // indexes of attributes and vendors positions
+    attribute_names: HashMap<String, usize>,
+    attribute_oids: HashMap<String, usize>,
+    attribute_codes: HashMap<AttributeKey, usize>,
+    vendor_names: HashMap<String, usize>,
+    vendor_ids: HashMap<u32, usize>,
+}
+
+impl Dictionary {
+    /// Creates a RadiusAttribute element starting from Dictionary's attribute name
+    /// attributes missing from the dictionary are accepted by their "Attr-" name, e.g. "Attr-26.10415.1"
+    pub fn create_attribute_by_name(&self, name: &str, value: Vec<u8>) -> Result<RadiusAttribute, String> {
+        match self.attribute_names.get(name) {
+            Some(&i) => Ok(RadiusAttribute::new(self.attributes[i].clone(), value)),
+            None => match name.strip_prefix("Attr-").and_then(|oid| self.unknown_attribute(oid)) {
+                Some(def) => Ok(RadiusAttribute::new(def, value)),
+                None => Err(format!("Unrecognized attribute name: {}", name)),
+            },
+        }
+    }
+
+    // synthetic definition of an attribute missing from the dictionary, by dotted identifier:
+    // "N", "26.VENDOR.N", "PARENT.N" (tlv or extended) or "EVS.VENDOR.N"
+    fn unknown_attribute(&self, oid: &str) -> Option<RadiusAttributeDef> {
+        let (prefix, code) = match oid.rfind('.') {
+            Some(n) => (&oid[..n], oid[(n + 1)..].parse::<u8>().ok()?),
+            None => return Some(RadiusAttributeDef::unknown(oid.parse::<u8>().ok()?, None, None)),
+        };
+        if let Some(&i) = self.attribute_oids.get(prefix) {
+            let parent = &self.attributes[i];
+            return Some(RadiusAttributeDef::unknown(code, parent.get_vendor().cloned(), Some(parent.clone())));
+        }
+
+        let n = prefix.rfind('.')?;
+        let vendor = self.get_vendor_by_id(prefix[(n + 1)..].parse::<u32>().ok()?)?.clone();
+        match &prefix[..n] {
+            "26" => Some(RadiusAttributeDef::unknown(code, Some(vendor), None)),
+            evs => {
+                let parent = &self.attributes[*self.attribute_oids.get(evs)?];
+                match parent.get_type() {
+                    RadiusAttributeType::Evs => Some(RadiusAttributeDef::unknown(code, Some(vendor), Some(parent.clone()))),
+                    _ => None,
+                }
+            },
+        }
+    }
+
+    /// Creates a RadiusAttribute element starting from Dictionary's attribute code
+    pub fn create_attribute_by_code(&self, code: u8, value: Vec<u8>) -> Result<RadiusAttribute, String> {
+        match self.find_attribute(None, None, code) {
+            Some(def) => Ok(RadiusAttribute::new(def.clone(), value)),
+            None => Err(format!("Unrecognized attribute code: {}", code)),
+        }
+    }
+
+    /// Creates a RadiusAttribute element starting from Dictionary's vendor id and vendor attribute code
+    pub fn create_vendor_attribute_by_code(&self, vendor: u32, code: u8, value: Vec<u8>) -> Result<RadiusAttribute, String> {
+        match self.find_attribute(None, Some(vendor), code) {
+            Some(def) => Ok(RadiusAttribute::new(def.clone(), value)),
+            None => Err(format!("Unrecognized vendor {} attribute code: {}", vendor, code)),
+        }
+    }
+
+    /// Creates a RadiusAttribute element starting from Dictionary's attribute dotted identifier, e.g. "241.1"
+    pub fn create_attribute_by_oid(&self, oid: &str, value: Vec<u8>) -> Result<RadiusAttribute, String> {
+        match self.attribute_oids.get(oid) {
+            Some(&i) => Ok(RadiusAttribute::new(self.attributes[i].clone(), value)),
+            None => Err(format!("Unrecognized attribute identifier: {}", oid)),
+        }
+    }
+
+    // looks for an attribute definition inside a parent (extended space) and a vendor
+    fn find_attribute(&self, parent: Option<&RadiusAttributeDef>, vendor: Option<u32>, code: u8) -> Option<&RadiusAttributeDef> {
+        self.attribute_codes.get(&(parent.map(|p| p.get_oid()), vendor, code)).map(|&i| &self.attributes[i])
+    }
+
+    /// Creates a RadiusAttribute element starting from Dictionary's attribute name and value name
+    pub fn create_attribute_by_value(&self, name: &str, value: &str) -> Result<RadiusAttribute, String> {
+        let number = self.get_value(name, value).ok_or_else(|| format!("Unrecognized value {} for attribute {}", value, name))?;
+        let mut attr = self.create_attribute_by_name(name, Vec::new())?;
+        let value = attr.get_definition().get_type().encode(&number.to_string())?;
+        attr.set_value(value);
+        Ok(attr)
+    }
+
+    /// Creates a RadiusAttribute element from a native value, e.g. `create_attribute("NAS-Port", 0u32)`
+    /// the value is encoded after the attribute data type, wrong widths and oversized values are rejected
+    pub fn create_attribute<T: IntoRadiusValue>(&self, name: &str, value: T) -> Result<RadiusAttribute, String> {
+        let mut attr = self.create_attribute_by_name(name, Vec::new())?;
+        let value = value.into_radius_value(attr.get_definition().get_type()).map_err(|e| format!("{}: {}", name, e))?.encode();
+        if value.len() > attr.get_max_length() && !attr.get_definition().get_flags().is_concat() {
+            return Err(format!("{}: value too long, {} bytes exceeding {}", name, value.len(), attr.get_max_length()));
+        }
+        attr.set_value(value);
+        Ok(attr)
+    }
+
+    /// Creates a tagged RadiusAttribute element from a native value, e.g. `create_tagged_attribute("Tunnel-Type", 1, 13u32)`
+    pub fn create_tagged_attribute<T: IntoRadiusValue>(&self, name: &str, tag: u8, value: T) -> Result<RadiusAttribute, String> {
+        self.create_attribute(name, value)?.with_tag(tag)
+    }
+
+    /// retrieves the number associated to a named value of an attribute
+    pub fn get_value(&self, attribute: &str, name: &str) -> Option<u32> {
+        self.values.get(attribute)?.iter().find(|v| v.get_name() == name).map(|v| v.get_value())
+    }
+
+    /// retrieves the name associated to a number of an attribute
+    pub fn get_value_name(&self, attribute: &str, value: u32) -> Option<&str> {
+        self.values.get(attribute)?.iter().find(|v| v.get_value() == value).map(|v| v.get_name())
+    }
+
+    /// retrieves all the named values of an attribute
+    pub fn get_values(&self, attribute: &str) -> &[RadiusValueDef] {
+        self.values.get(attribute).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// retrieves the value name of an integer RadiusAttribute, if any
+    pub fn get_attribute_value_name(&self, attr: &RadiusAttribute) -> Option<&str> {
+        let value = attr.get_value();
+        match attr.get_definition().get_type() {
+            RadiusAttributeType::Byte | RadiusAttributeType::Short | RadiusAttributeType::Integer if value.len() <= 4 => {
+                let number = value.iter().fold(0u32, |acc, b| (acc << 8) | u32::from(*b));
+                self.get_value_name(attr.get_definition().get_name(), number)
+            },
+            _ => None,
+        }
+    }
+
+    /// radclient style `Name = value` text of a RadiusAttribute, integer values named after VALUE rows
+    /// tlv children are listed, separated by commas, instead of their parent
+    pub fn format_attribute(&self, attr: &RadiusAttribute) -> String {
+        if !attr.get_children().is_empty() {
+            return attr.get_children().iter().map(|c| self.format_attribute(c)).collect::<Vec<String>>().join(", ");
+        }
+        match self.get_attribute_value_name(attr) {
+            Some(name) => format!("{} = {}", attr.get_text_name(), name),
+            None => attr.to_string(),
+        }
+    }
+
+    /// radclient style listing of the attributes of a RadiusData, one per line
+    pub fn format_data(&self, data: &RadiusData) -> String {
+        data.get_attributes().iter().map(|a| self.format_attribute(a) + "\n").collect()
+    }
+
+    /// parses a radclient style attribute list, e.g. `User-Name = "testing", Service-Type = Framed-User`
+    /// attributes are separated by commas or new lines, `#` starts a comment line
+    /// values may be VALUE names, quoted strings with `\` escapes, `0x` hexadecimal bytes or
+    /// the text representation of the attribute type, has_tag attributes accept a `Name:tag` form
+    /// attributes defined inside a tlv are returned standalone, see `RadiusData::add_nested_attribute`
+    pub fn parse_attributes(&self, text: &str) -> Result<Vec<RadiusAttribute>, String> {
+        let mut items = Vec::new();
+        let mut item = String::new();
+        let (mut quoted, mut escaped) = (false, false);
+        for c in text.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                ',' | '\n' if !quoted => {
+                    items.push(std::mem::take(&mut item));
+                    continue;
+                },
+                _ => {},
+            }
+            item.push(c);
+        }
+        if quoted {
+            return Err(format!("Unterminated quoted value: {}", item.trim()));
+        }
+        items.push(item);
+
+        items.iter().map(|i| i.trim()).filter(|i| !i.is_empty() && !i.starts_with('#')).map(|i| self.parse_attribute_text(i)).collect()
+    }
+
+    // parses a single `Name[:tag] = value` item
+    fn parse_attribute_text(&self, item: &str) -> Result<RadiusAttribute, String> {
+        let mut parts = item.splitn(2, '=');
+        let (name, value) = match (parts.next().map(|p| p.trim()), parts.next().map(|p| p.trim())) {
+            (Some(name), Some(value)) if !name.is_empty() => (name, value),
+            _ => return Err(format!("Malformed attribute: {}", item)),
+        };
+        let (name, tag) = match name.rfind(':') {
+            Some(n) => (&name[..n], Some(name[(n + 1)..].parse::<u8>().map_err(|_| format!("Malformed tag: {}", item))?)),
+            None => (name, None),
+        };
+
+        let atype = self.create_attribute_by_name(name, Vec::new())?.get_definition().get_type().clone();
+        let attr = if value.starts_with('"') {
+            let s = Self::unquote(value).ok_or_else(|| format!("Malformed quoted value: {}", item))?;
+            match atype {
+                RadiusAttributeType::String | RadiusAttributeType::Octets => self.create_attribute(name, s.into_bytes()),
+                _ => self.create_attribute(name, s),
+            }
+        }
+        else if value.starts_with("0x") || value.starts_with("0X") {
+            let bytes = (2..value.len()).step_by(2)
+                .map(|i| value.get(i..(i + 2)).and_then(|b| u8::from_str_radix(b, 16).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .filter(|_| value.len() % 2 == 0)
+                .ok_or_else(|| format!("Malformed hexadecimal value: {}", item))?;
+            self.create_attribute(name, bytes)
+        }
+        else {
+            match self.get_value(name, value) {
+                Some(number) => self.create_attribute(name, number),
+                None => self.create_attribute(name, value),
+            }
+        }?;
+
+        match tag {
+            Some(tag) => attr.with_tag(tag).map_err(|e| format!("{}: {}", e, item)),
+            None => Ok(attr),
+        }
+    }
+
+    // content of a double quoted string, with \\ \" \n \r \t escapes
+    fn unquote(s: &str) -> Option<String> {
+        let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+        let mut res = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            res.push(match c {
+                '\\' => match chars.next()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    c => c,
+                },
+                '"' => return None,
+                c => c,
+            });
+        }
+        Some(res)
+    }
+
+    /// deserializes a RadiusData serialized with serde, encoding attribute values after their definitions
+    /// integer values may also be given by VALUE name, attributes defined inside a tlv are nested back
+    #[cfg(feature = "serde")]
+    pub fn deserialize_data<'de, D: serde::Deserializer<'de>>(&self, deserializer: D) -> Result<RadiusData, D::Error> {
+        use serde::de::Error;
+        use std::convert::TryFrom;
+
+        let entry = <DataEntry as serde::Deserialize>::deserialize(deserializer)?;
+        let code = RadiusCode::from(entry.code);
+        let authenticator = from_hex(&entry.authenticator).ok_or_else(|| D::Error::custom(format!("Malformed authenticator: {}", entry.authenticator)))?;
+
+        let mut res = RadiusData::new(code, Some(entry.identifier), Some(authenticator), None);
+        for a in entry.attributes {
+            let name = a.name;
+            let mut attr = match (a.hex, a.value) {
+                (Some(hex), _) => {
+                    let value = from_hex(&hex).ok_or_else(|| D::Error::custom(format!("{}: malformed hexadecimal value: {}", name, hex)))?;
+                    self.create_attribute_by_name(&name, value)
+                },
+                (None, Some(EntryValue::Unsigned(n))) => self.create_attribute(&name, n),
+                (None, Some(EntryValue::Signed(n))) => i32::try_from(n).map_err(|_| format!("{}: value out of range: {}", name, n)).and_then(|n| self.create_attribute(&name, n)),
+                (None, Some(EntryValue::Text(s))) => match self.get_value(&name, &s) {
+                    Some(n) => self.create_attribute(&name, n),
+                    None => self.create_attribute(&name, s),
+                },
+                (None, None) => Err(format!("{}: missing value", name)),
+            }.map_err(D::Error::custom)?;
+            attr.set_tag(a.tag);
+            res.add_nested_attribute(attr).map_err(D::Error::custom)?;
+        }
+        Ok(res)
+    }
+
+    /// retrieves all the attribute definitions, in load order
+    pub fn get_attributes(&self) -> &[RadiusAttributeDef] {
+        &self.attributes
+    }
+
+    /// retrieves all the vendor definitions, in load order
+    pub fn get_vendors(&self) -> &[RadiusVendorDef] {
+        &self.vendors
+    }
+
+    /// retrieves a vendor definition by name
+    pub fn get_vendor_by_name(&self, name: &str) -> Option<&RadiusVendorDef> {
+        self.vendor_names.get(name).map(|&i| &self.vendors[i])
+    }
+
+    /// retrieves a vendor definition by id
+    pub fn get_vendor_by_id(&self, id: u32) -> Option<&RadiusVendorDef> {
+        self.vendor_ids.get(&id).map(|&i| &self.vendors[i])
+    }
+
+    /// converts a byte array into a RadiusData element
+    /// untrusted input is fully validated: header length, attribute lengths and nested structures,
+    /// bytes beyond the header length are ignored as padding,
+    /// consecutive fragments of concat and long extended attributes are reassembled
+    pub fn load_bytes(&self, data: &[u8]) -> Result<RadiusData, PacketError> {
+        if data.len() < 20 {
+            return Err(PacketError::new(0, format!("Packet too short: {} bytes", data.len())));
+        }
+        let len = u16::from_be_bytes([data[2], data[3]]) as usize;
+        if len < 20 || len > data.len() || len > MAX_PACKET_LENGTH {
+            return Err(PacketError::new(2, format!("Invalid packet length: {}, {} bytes received", len, data.len())));
+        }
+        let data = &data[..len];
+        let code = RadiusCode::from(data[0]);
+
+        let mut attributes: Vec<RadiusAttribute> = Vec::new();
+        // concat attribute being reassembled
+        let mut concat: Option<usize> = None;
+        let mut i = 20;
+        while i < data.len() {
+            let start = i;
+            let code = data[i];
+            let size = match data.get(i + 1) {
+                Some(&size) => size as usize,
+                None => return Err(PacketError::new(i, String::from("Truncated attribute header"))),
+            };
+            if size < 2 || i + size > data.len() {
+                return Err(PacketError::new(i + 1, format!("Invalid attribute length: {}", size)));
+            }
+            let mut value = data[(i + 2)..(i + size)].to_vec();
+            let mut fragments = Vec::new();
+            i += size;
+
+            // consecutive fragments of a concat attribute are reassembled, their sizes being kept to encode them back the same way
+            if let Some(c) = concat.take() {
+                if attributes[c].get_definition().get_code() == code {
+                    attributes[c].append_fragment(&value);
+                    concat = Some(c);
+                    continue;
+                }
+            }
+
+            let at = |e: String| PacketError::new(start, e);
+            let decoded = if code == VENDOR_SPECIFIC {
+                self.load_vendor_specific(&value).map_err(at)?
+            }
+            else {
+                let def = match self.find_attribute(None, None, code) {
+                    Some(def) => def,
+                    // unknown attributes are kept raw, to be encoded back unchanged
+                    None => {
+                        attributes.push(RadiusAttribute::new(RadiusAttributeDef::unknown(code, None, None), value));
+                        continue;
+                    },
+                };
+                match def.get_type() {
+                    RadiusAttributeType::Extended | RadiusAttributeType::LongExtended if def.get_type().validate(&value).is_err() => {
+                        return Err(at(format!("Malformed {} attribute", def.get_name())));
+                    },
+                    RadiusAttributeType::Extended => self.load_extended(def, &value[..1], &value[1..]).map_err(at)?,
+                    RadiusAttributeType::LongExtended => {
+                        // fragments flagged with "M" are followed by the rest of the value
+                        fragments.push(value.len() - 2);
+                        while value[1] & LONG_EXTENDED_MORE != 0 && i + 4 <= data.len() && data[i] == code && data[i + 1] >= 4
+                            && i + data[i + 1] as usize <= data.len() && data[i + 2] == value[0] {
+                            let next = data[i + 1] as usize;
+                            value[1] = data[i + 3];
+                            value.extend_from_slice(&data[(i + 4)..(i + next)]);
+                            fragments.push(next - 4);
+                            i += next;
+                        }
+                        self.load_extended(def, &value[..1], &value[2..]).map_err(at)?
+                    },
+                    _ => RadiusAttribute::from_wire(def.clone(), &value),
+                }
+            };
+
+            for mut attr in decoded {
+                attr.set_container(start);
+                attr.set_fragments(fragments.clone());
+                self.load_tlv(&mut attr).map_err(at)?;
+                // reassembled long extended values may exceed what their type allows
+                attr.check_length().map_err(at)?;
+                if code != VENDOR_SPECIFIC && attr.get_definition().get_parent().is_none() && attr.get_definition().get_flags().is_concat() {
+                    concat = Some(attributes.len());
+                }
+                attributes.push(attr);
+            }
+        }
+
+        Ok(RadiusData::new(code, Some(data[1]), Some(data[4..20].to_vec()), Some(attributes)))
+    }
+
+    /// loads a Dictionary from a file, resolving $INCLUDE rows relative to the including file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Dictionary, DictionaryError> {
+        let mut res = Dictionary::empty();
+        res.parse_file(path.as_ref(), &mut ParseContext::new(false))?;
+        Ok(res)
+    }
+
+    /// loads a Dictionary from a directory
+    /// The `dictionary` file is used as entry point when present (FreeRADIUS layout),
+    /// otherwise every file in the directory is loaded in name order
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<Dictionary, DictionaryError> {
+        let mut res = Dictionary::empty();
+        res.parse_dir(path.as_ref(), &mut ParseContext::new(false))?;
+        Ok(res)
+    }
+
+    /// validates dictionary text in strict mode, returning every problem found
+    /// On top of loading errors, strict mode reports unknown keywords, duplicated definitions,
+    /// VALUE rows of undeclared attributes and values not fitting their attribute type
+    pub fn validate(text: &str) -> Vec<DictionaryError> {
+        let mut ctx = ParseContext::new(true);
+        // strict parsing collects problems instead of failing
+        let _ = Dictionary::empty().parse(text, None, &mut ctx);
+        ctx.errors
+    }
+
+    /// validates a dictionary file in strict mode, included files too
+    pub fn validate_file<P: AsRef<Path>>(path: P) -> Vec<DictionaryError> {
+        let mut ctx = ParseContext::new(true);
+        let _ = Dictionary::empty().parse_file(path.as_ref(), &mut ctx);
+        ctx.errors
+    }
+
+    /// validates a dictionary directory in strict mode, see `from_dir`
+    pub fn validate_dir<P: AsRef<Path>>(path: P) -> Vec<DictionaryError> {
+        let mut ctx = ParseContext::new(true);
+        let _ = Dictionary::empty().parse_dir(path.as_ref(), &mut ctx);
+        ctx.errors
+    }
+
+    /// adds a bundled vendor dictionary, e.g. `Dictionary::default().with_vendor(Vendor::ThreeGpp)?`
+    /// fails when its definitions conflict with the ones already loaded
+    pub fn with_vendor(mut self, vendor: Vendor) -> Result<Dictionary, DictionaryError> {
+        self.parse(vendor.get_dictionary(), None, &mut ParseContext::new(false))?;
+        Ok(self)
+    }
+
+    /// loads a Dictionary from the IANA "radius-types" CSV registry (Value, Description, Data Type, Reference)
+    /// RFC 8044 data types are mapped to dictionary ones, rows without a data type
+    /// (unassigned, reserved, ranges) are skipped
+    pub fn from_iana_csv(text: &str) -> Result<Dictionary, DictionaryError> {
+        let mut res = Dictionary::empty();
+        for record in csv_records(text) {
+            let field = |i: usize| record.get(i).map(|(_, _, f)| f.trim()).unwrap_or("");
+            let (value, name, dtype) = (field(0), field(1), field(2));
+            if dtype.is_empty() || value == "Value" || value.contains(|c: char| !c.is_ascii_digit() && c != '.') {
+                continue;
+            }
+
+            let (atype, flags) = match dtype {
+                "text" => ("string", None),
+                "string" => ("octets", None),
+                "concat" => ("octets", Some("concat")),
+                "enum" => ("integer", None),
+                "time" => ("date", None),
+                "ipv4addr" => ("ipaddr", None),
+                _ => (dtype, None),
+            };
+            let mut parts = vec!["ATTRIBUTE", name, value, atype];
+            parts.extend(flags);
+            // row fields back to CSV fields: name, value, data type
+            let at = |(n, message): RowError| {
+                let (line, column, _) = record[[1, 1, 0, 2, 2][n]];
+                DictionaryError::new(None, line, column, message)
+            };
+            let def = res.parse_attribute(&parts, &None).map_err(at)?;
+            res.add_attribute(def, false).map_err(at)?;
+        }
+        Ok(res)
+    }
+
+    /// parses dictionary text on top of the current definitions, e.g. to add site specific attributes
+    pub fn extend_from_str(&mut self, text: &str) -> Result<(), DictionaryError> {
+        self.parse(text, None, &mut ParseContext::new(false))
+    }
+
+    /// parses an ATTRIBUTE row against the current definitions, resolving dotted codes of vendor and extended attributes
+    pub fn parse_attribute_row(&self, row: &str) -> Result<RadiusAttributeDef, String> {
+        let parts: Vec<&str> = row.split_whitespace().collect();
+        if parts.first() != Some(&"ATTRIBUTE") {
+            return Err(format!("Not an attribute row: {}", row));
+        }
+        self.parse_attribute(&parts, &None).map_err(|(_, e)| e)
+    }
+
+    /// registers an attribute definition, failing on conflicts with the current ones
+    pub fn register_attribute(&mut self, def: RadiusAttributeDef) -> Result<(), String> {
+        self.add_attribute(def, false).map_err(|(_, e)| e)
+    }
+
+    /// registers an attribute definition, replacing the ones with the same name or code
+    /// nested definitions follow the new one, those it cannot contain anymore are removed too
+    /// returns the replaced and removed definitions
+    pub fn override_attribute(&mut self, def: RadiusAttributeDef) -> Vec<RadiusAttributeDef> {
+        let mut replaced: Vec<usize> = self.attribute_names.get(def.get_name()).into_iter()
+            .chain(self.attribute_codes.get(&Self::attribute_key(&def)))
+            .cloned()
+            .collect();
+        replaced.sort_unstable();
+        replaced.dedup();
+
+        let mut res: Vec<RadiusAttributeDef> = replaced.iter().rev().map(|&i| self.attributes.remove(i)).rev().collect();
+        self.attributes.push(def);
+        self.reindex();
+        res.append(&mut self.remove_orphans());
+        self.relink();
+        res
+    }
+
+    /// removes an attribute definition by name, along with its named values
+    /// and the definitions nested in it, e.g. the sub-attributes of a tlv
+    pub fn remove_attribute(&mut self, name: &str) -> Option<RadiusAttributeDef> {
+        let i = *self.attribute_names.get(name)?;
+        self.values.remove(name);
+        let res = self.attributes.remove(i);
+        self.reindex();
+        self.remove_orphans();
+        Some(res)
+    }
+
+    /// registers a vendor definition, failing on conflicts with the current ones
+    pub fn register_vendor(&mut self, vendor: RadiusVendorDef) -> Result<(), String> {
+        self.add_vendor(vendor, false).map_err(|(_, e)| e)
+    }
+
+    /// registers a named value, failing if the name is already bound to another number
+    pub fn register_value(&mut self, value: RadiusValueDef) -> Result<(), String> {
+        self.add_value(value, false).map_err(|(_, e)| e)
+    }
+
+    /// merges the definitions of another Dictionary, conflicts are solved by precedence
+    /// the Dictionary is left untouched when the merge fails
+    pub fn merge(&mut self, other: Dictionary, precedence: Precedence) -> Result<(), String> {
+        let mut res = self.clone();
+        res.merge_into(other, precedence)?;
+        *self = res;
+        Ok(())
+    }
+
+    fn merge_into(&mut self, other: Dictionary, precedence: Precedence) -> Result<(), String> {
+        // values in attributes order, for a deterministic outcome
+        let values: Vec<RadiusValueDef> = other.sorted_values().into_iter().cloned().collect();
+
+        for vendor in other.vendors {
+            if let Err((_, e)) = self.add_vendor(vendor.clone(), false) {
+                match precedence {
+                    Precedence::Existing => {},
+                    Precedence::Incoming => self.override_vendor(vendor)?,
+                    Precedence::Strict => return Err(e),
+                }
+            }
+        }
+
+        for def in other.attributes {
+            // definitions of the other Dictionary hold its own vendor and parent copies
+            let def = self.relinked(def);
+            if let Err((_, e)) = self.add_attribute(def.clone(), false) {
+                match precedence {
+                    Precedence::Existing => {},
+                    Precedence::Incoming => {
+                        self.override_attribute(def);
+                    },
+                    Precedence::Strict => return Err(e),
+                }
+            }
+        }
+
+        if precedence == Precedence::Incoming {
+            // existing values sharing the name or the number of an incoming one are replaced,
+            // so that neither of them is ambiguous, incoming aliases are kept
+            for value in values.iter() {
+                if let Some(existing) = self.values.get_mut(value.get_attribute()) {
+                    existing.retain(|v| values.contains(v) || (v.get_name() != value.get_name() && v.get_value() != value.get_value()));
+                }
+            }
+        }
+        for value in values {
+            if let Err((_, e)) = self.add_value(value, false) {
+                match precedence {
+                    Precedence::Existing => {},
+                    Precedence::Incoming | Precedence::Strict => return Err(e),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // replaces the vendor conflicting with an incoming one, by name or by id
+    // attributes of a vendor replaced under another id go away with it
+    fn override_vendor(&mut self, vendor: RadiusVendorDef) -> Result<(), String> {
+        let by_name = self.vendor_names.get(vendor.get_name()).cloned();
+        let by_id = self.vendor_ids.get(&vendor.get_id()).cloned();
+        let i = match (by_name, by_id) {
+            (Some(n), Some(i)) if n != i => return Err(format!("Vendor {} ({}) conflicts with {} ({}) and {} ({})", vendor.get_name(), vendor.get_id(),
+                self.vendors[n].get_name(), self.vendors[n].get_id(), self.vendors[i].get_name(), self.vendors[i].get_id())),
+            (Some(i), _) | (None, Some(i)) => i,
+            (None, None) => return self.add_vendor(vendor, false).map_err(|(_, e)| e),
+        };
+
+        let id = self.vendors[i].get_id();
+        if id != vendor.get_id() {
+            let names: Vec<String> = self.attributes.iter().filter(|def| def.get_vendor().map(|v| v.get_id()) == Some(id)).map(|def| def.get_name().to_owned()).collect();
+            for name in names {
+                self.remove_attribute(&name);
+            }
+        }
+        self.vendors[i] = vendor;
+        self.reindex();
+        self.relink();
+        Ok(())
+    }
+
+    /// JSON representation of the definitions:
+    /// `{"vendors": [..], "attributes": [..], "values": [..]}`, see the test for the entries layout
+    pub fn to_json(&self) -> String {
+        let vendors: Vec<String> = self.vendors.iter().map(|v| format!(
+            "{{\"name\": {}, \"id\": {}, \"type_size\": {}, \"length_size\": {}, \"continuation\": {}}}",
+            json_string(v.get_name()), v.get_id(), v.get_type_size(), v.get_length_size(), v.has_continuation(),
+        )).collect();
+        let attributes: Vec<String> = self.attributes.iter().map(|def| {
+            let flags = def.get_flags();
+            format!(
+                "{{\"name\": {}, \"oid\": {}, \"code\": {}, \"type\": {}, \"vendor\": {}, \"parent\": {}, \"flags\": {{\"has_tag\": {}, \"encrypt\": {}, \"concat\": {}, \"array\": {}}}}}",
+                json_string(def.get_name()), json_string(&def.get_oid()), def.get_code(), json_string(def.get_type().get_name()),
+                def.get_vendor().map(|v| v.get_id().to_string()).unwrap_or_else(|| "null".to_owned()),
+                def.get_parent().map(|p| json_string(&p.get_oid())).unwrap_or_else(|| "null".to_owned()),
+                flags.has_tag(), flags.get_encrypt().map(|e| e.to_u8().to_string()).unwrap_or_else(|| "null".to_owned()), flags.is_concat(), flags.is_array(),
+            )
+        }).collect();
+        let values: Vec<String> = self.sorted_values().iter().map(|v| format!(
+            "{{\"attribute\": {}, \"name\": {}, \"value\": {}}}",
+            json_string(v.get_attribute()), json_string(v.get_name()), v.get_value(),
+        )).collect();
+
+        let section = |entries: Vec<String>| match entries.is_empty() {
+            true => "[]".to_owned(),
+            false => format!("[\n    {}\n  ]", entries.join(",\n    ")),
+        };
+        format!("{{\n  \"vendors\": {},\n  \"attributes\": {},\n  \"values\": {}\n}}\n", section(vendors), section(attributes), section(values))
+    }
+
+    // named values following attributes order, values of unknown attributes last
+    fn sorted_values(&self) -> Vec<&RadiusValueDef> {
+        let mut orphans: Vec<&String> = self.values.keys().filter(|name| !self.attribute_names.contains_key(*name)).collect();
+        orphans.sort();
+        self.attributes.iter().map(|def| def.get_name())
+            .chain(orphans.into_iter().map(|name| name.as_str()))
+            .flat_map(|name| self.get_values(name))
+            .collect()
+    }
+
+    // vendor block of a vendor attribute, with its Extended-Vendor-Specific ancestor if any
+    fn vendor_block(def: &RadiusAttributeDef) -> Option<(&RadiusVendorDef, Option<&RadiusAttributeDef>)> {
+        let vendor = def.get_vendor()?;
+        let mut parent = def.get_parent();
+        while let Some(p) = parent {
+            if *p.get_type() == RadiusAttributeType::Evs {
+                return Some((vendor, Some(p)));
+            }
+            parent = p.get_parent();
+        }
+        Some((vendor, None))
+    }
+
+    fn empty() -> Dictionary {
+        Dictionary {
+            attributes: Vec::new(),
+            vendors: Vec::new(),
+            values: HashMap::new(),
+            attribute_names: HashMap::new(),
+            attribute_oids: HashMap::new(),
+            attribute_codes: HashMap::new(),
+            vendor_names: HashMap::new(),
+            vendor_ids: HashMap::new(),
+        }
+    }
+
+    // conflicting redefinitions are errors, identical ones are ignored unless strict
+    fn add_attribute(&mut self, def: RadiusAttributeDef, strict: bool) -> Result<(), RowError> {
+        if let Some(&i) = self.attribute_names.get(def.get_name()) {
+            if self.attributes[i] != def {
+                return Err((1, format!("Conflicting definition of attribute {}", def.get_name())));
+            }
+            if strict {
+                return Err((1, format!("Duplicate definition of attribute {}", def.get_name())));
+            }
+            return Ok(());
+        }
+        let key = Self::attribute_key(&def);
+        if let Some(&i) = self.attribute_codes.get(&key) {
+            return Err((2, format!("Attribute {} conflicts with {} ({})", def.get_name(), self.attributes[i].get_name(), def.get_oid())));
+        }
+
+        let i = self.attributes.len();
+        self.attribute_names.insert(def.get_name().to_owned(), i);
+        self.attribute_oids.insert(def.get_oid(), i);
+        self.attribute_codes.insert(key, i);
+        self.attributes.push(def);
+        Ok(())
+    }
+
+    fn attribute_key(def: &RadiusAttributeDef) -> AttributeKey {
+        (def.get_parent().map(|p| p.get_oid()), def.get_vendor().map(|v| v.get_id()), def.get_code())
+    }
+
+    // removes the definitions whose parent is missing or cannot contain them anymore
+    fn remove_orphans(&mut self) -> Vec<RadiusAttributeDef> {
+        let mut res = Vec::new();
+        loop {
+            let orphan = self.attributes.iter().position(|def| def.get_parent().map_or(false, |p| {
+                match self.attribute_oids.get(&p.get_oid()).map(|&i| self.attributes[i].get_type()) {
+                    Some(RadiusAttributeType::Extended) | Some(RadiusAttributeType::LongExtended) | Some(RadiusAttributeType::Tlv) => false,
+                    Some(RadiusAttributeType::Evs) => def.get_vendor().is_none(),
+                    _ => true,
+                }
+            }));
+            match orphan {
+                Some(i) => {
+                    let def = self.attributes.remove(i);
+                    self.values.remove(def.get_name());
+                    self.reindex();
+                    res.push(def);
+                },
+                None => return res,
+            }
+        }
+    }
+
+    // refreshes the vendor and parent copies held by the definitions after a replacement
+    fn relink(&mut self) {
+        // parents first, so that children copy up to date ones
+        let depth = |def: &RadiusAttributeDef| std::iter::successors(def.get_parent(), |p| p.get_parent()).count();
+        let mut order: Vec<usize> = (0..self.attributes.len()).collect();
+        order.sort_by_key(|&i| depth(&self.attributes[i]));
+        for i in order {
+            let def = self.attributes[i].clone();
+            self.attributes[i] = self.relinked(def);
+        }
+    }
+
+    // definition with the vendor and parent copies of this Dictionary, when it knows them
+    fn relinked(&self, mut def: RadiusAttributeDef) -> RadiusAttributeDef {
+        if let Some(&i) = def.get_vendor().and_then(|v| self.vendor_ids.get(&v.get_id())) {
+            def.set_vendor(self.vendors[i].clone());
+        }
+        if let Some(&i) = def.get_parent().and_then(|p| self.attribute_oids.get(&p.get_oid())) {
+            def.set_parent(self.attributes[i].clone());
+        }
+        def
+    }
+
+    // rebuilds the indexes after a removal
+    fn reindex(&mut self) {
+        self.attribute_names = self.attributes.iter().enumerate().map(|(i, def)| (def.get_name().to_owned(), i)).collect();
+        self.attribute_oids = self.attributes.iter().enumerate().map(|(i, def)| (def.get_oid(), i)).collect();
+        self.attribute_codes = self.attributes.iter().enumerate().map(|(i, def)| (Self::attribute_key(def), i)).collect();
+        self.vendor_names = self.vendors.iter().enumerate().map(|(i, v)| (v.get_name().to_owned(), i)).collect();
+        self.vendor_ids = self.vendors.iter().enumerate().map(|(i, v)| (v.get_id(), i)).collect();
+    }
+
+    fn add_vendor(&mut self, vendor: RadiusVendorDef, strict: bool) -> Result<(), RowError> {
+        let known = self.vendor_names.get(vendor.get_name()).or_else(|| self.vendor_ids.get(&vendor.get_id()));
+        if let Some(&i) = known {
+            if self.vendors[i] != vendor {
+                return Err((1, format!("Vendor {} ({}) conflicts with {} ({})", vendor.get_name(), vendor.get_id(), self.vendors[i].get_name(), self.vendors[i].get_id())));
+            }
+            if strict {
+                return Err((1, format!("Duplicate definition of vendor {}", vendor.get_name())));
+            }
+            return Ok(());
+        }
+
+        let i = self.vendors.len();
+        self.vendor_names.insert(vendor.get_name().to_owned(), i);
+        self.vendor_ids.insert(vendor.get_id(), i);
+        self.vendors.push(vendor);
+        Ok(())
+    }
 
-impl Dictionary {
-    /// Creates a RadiusAttribute element starting from Dictionary's attribute name
-    pub fn create_attribute_by_name(&self, name: &str, value: Vec<u8>) -> Result<RadiusAttribute, String> {
-        for def in self.attributes.iter() {
-            if def.get_name() == name {
-                return Ok(RadiusAttribute::new(def.clone(), value));
+    // values sharing a number are aliases, a name bound to different numbers is a conflict
+    fn add_value(&mut self, value: RadiusValueDef, strict: bool) -> Result<(), RowError> {
+        if strict {
+            let index = self.attribute_names.get(value.get_attribute());
+            let def = index.map(|&i| &self.attributes[i]).ok_or_else(|| (1, format!("Value {} of undeclared attribute {}", value.get_name(), value.get_attribute())))?;
+            let max = match def.get_type() {
+                RadiusAttributeType::Byte => u32::from(u8::MAX),
+                RadiusAttributeType::Short => u32::from(u16::MAX),
+                RadiusAttributeType::Integer | RadiusAttributeType::Integer64 | RadiusAttributeType::Signed => u32::MAX,
+                atype => return Err((1, format!("Value {} of {} attribute {}", value.get_name(), atype.get_name(), value.get_attribute()))),
+            };
+            if value.get_value() > max {
+                return Err((3, format!("Value {} out of {} attribute {} range", value.get_value(), def.get_type().get_name(), value.get_attribute())));
             }
         }
 
-        Err(format!("Unrecognized attribute name: {}", name))
+        let values = self.values.entry(value.get_attribute().to_owned()).or_default();
+        match values.iter().find(|v| v.get_name() == value.get_name()) {
+            Some(v) if v.get_value() != value.get_value() => Err((2, format!("Conflicting value {} of attribute {}: {} and {}", value.get_name(), value.get_attribute(), v.get_value(), value.get_value()))),
+            Some(_) if strict => Err((2, format!("Duplicate value {} of attribute {}", value.get_name(), value.get_attribute()))),
+            Some(_) => Ok(()),
+            None => {
+                values.push(value);
+                Ok(())
+            },
+        }
     }
 
-    /// Creates a RadiusAttribute element starting from Dictionary's attribute code
-    pub fn create_attribute_by_code(&self, code: u8, value: Vec<u8>) -> Result<RadiusAttribute, String> {
-        for def in self.attributes.iter() {
-            if def.get_code() == code {
-                return Ok(RadiusAttribute::new(def.clone(), value));
+    fn parse_dir(&mut self, path: &Path, ctx: &mut ParseContext) -> Result<(), DictionaryError> {
+        let main = path.join("dictionary");
+        if main.is_file() {
+            return self.parse_file(&main, ctx);
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => return ctx.report(DictionaryError::new(Some(path), 0, 0, e.to_string())),
+        };
+        let mut files: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|e| e.path())).filter(|p| p.is_file()).collect();
+        files.sort();
+
+        for file in files {
+            self.parse_file(&file, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn parse_file(&mut self, path: &Path, ctx: &mut ParseContext) -> Result<(), DictionaryError> {
+        let error = |message: String| DictionaryError::new(Some(path), 0, 0, message);
+        let canonical = match path.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(e) => return ctx.report(error(e.to_string())),
+        };
+        if ctx.stack.contains(&canonical) {
+            return ctx.report(error("include cycle detected".to_owned()));
+        }
+        let text = match fs::read_to_string(&canonical) {
+            Ok(text) => text,
+            Err(e) => return ctx.report(error(e.to_string())),
+        };
+
+        ctx.stack.push(canonical);
+        let res = self.parse(&text, Some(path), ctx);
+        ctx.stack.pop();
+        res
+    }
+
+    // parses dictionary text, included files errors are located in the included file
+    fn parse(&mut self, text: &str, file: Option<&Path>, ctx: &mut ParseContext) -> Result<(), DictionaryError> {
+        let mut block: Option<VendorBlock> = None;
+        for (n, raw) in text.lines().enumerate() {
+            let l = match raw.find('#') {
+                Some(pos) => &raw[..pos],
+                None => raw,
+            }.trim();
+            let at = |(field, message): RowError| DictionaryError::new(file, n + 1, column(raw, field), message);
+
+            let mut parts = l.split_whitespace();
+            match parts.next() {
+                Some(keyword) if keyword == "$INCLUDE" || keyword == "$INCLUDE-" => {
+                    let name = match parts.next() {
+                        Some(name) => name,
+                        None => {
+                            ctx.report(at((0, format!("Malformed include row: {}", l))))?;
+                            continue;
+                        },
+                    };
+                    let path = match file.and_then(|f| f.parent()) {
+                        Some(dir) => dir.join(name),
+                        None => PathBuf::from(name),
+                    };
+                    // "$INCLUDE-" silently skips missing files
+                    if !path.exists() {
+                        if keyword == "$INCLUDE" {
+                            ctx.report(at((1, format!("Missing included file: {}", path.display()))))?;
+                        }
+                        continue;
+                    }
+                    self.parse_file(&path, ctx)?;
+                },
+                _ => {
+                    if let Err(e) = self.parse_row(l, &mut block, ctx.strict) {
+                        ctx.report(at(e))?;
+                    }
+                },
             }
         }
 
-        Err(format!("Unrecognized attribute code: {}", code))
+        match block {
+            Some((vendor, _)) => ctx.report(DictionaryError::new(file, text.lines().count().max(1), 1, format!("Unterminated vendor block: {}", vendor.get_name()))),
+            None => Ok(()),
+        }
     }
 
-    /// converts a byte array into a RadiusData element
-    pub fn load_bytes(&self, data: &[u8]) -> Result<RadiusData, String> {
-        let mut attributes = Vec::new();
-        let mut i = 20;
-        while i < data.len() {
-            let size = data[i + 1] as usize;
-            attributes.push(self.create_attribute_by_code(data[i], data[(i + 2)..(i + size)].to_vec())?);
+    // errors carry the index of the offending field
+    fn parse_row(&mut self, l: &str, block: &mut Option<VendorBlock>, strict: bool) -> Result<(), RowError> {
+        let parts: Vec<&str> = l.split_whitespace().collect();
+        match parts.first() {
+            Some(&"ATTRIBUTE") => {
+                let def = self.parse_attribute(&parts, block)?;
+                self.add_attribute(def, strict)?;
+            },
+            Some(&"VENDOR") => {
+                let vendor = RadiusVendorDef::from_str(l).map_err(|e| match parts.get(2).map(|id| id.parse::<u32>()) {
+                    Some(Ok(_)) => (3, e),
+                    Some(Err(_)) => (2, e),
+                    None => (0, e),
+                })?;
+                self.add_vendor(vendor, strict)?;
+            },
+            Some(&"VALUE") => {
+                let value = RadiusValueDef::from_str(l).map_err(|e| (if parts.len() == 4 { 3 } else { 0 }, e))?;
+                self.add_value(value, strict)?;
+            },
+            Some(&"BEGIN-VENDOR") => {
+                let name = parts.get(1).ok_or_else(|| (0, format!("Malformed begin vendor row: {}", l)))?;
+                if block.is_some() {
+                    return Err((0, format!("Nested vendor block: {}", l)));
+                }
+                let vendor = self.get_vendor_by_name(name).ok_or_else(|| (1, format!("Unrecognized vendor name: {}", name)))?.clone();
+                // "format=Extended-Vendor-Specific-N" moves the block in an extended space
+                let parent = match parts.get(2) {
+                    Some(format) => {
+                        let parent = format.strip_prefix("format=")
+                            .and_then(|name| self.attribute_names.get(name).map(|&i| &self.attributes[i]))
+                            .filter(|def| *def.get_type() == RadiusAttributeType::Evs)
+                            .ok_or_else(|| (2, format!("Malformed begin vendor format: {}", l)))?;
+                        Some(parent.clone())
+                    },
+                    None => None,
+                };
+                *block = Some((vendor, parent));
+            },
+            Some(&"END-VENDOR") => {
+                let name = parts.get(1).ok_or_else(|| (0, format!("Malformed end vendor row: {}", l)))?;
+                match block {
+                    Some((ref vendor, _)) if vendor.get_name() == *name => *block = None,
+                    _ => return Err((1, format!("Unexpected end vendor row: {}", l))),
+                }
+            },
+            Some(keyword) if strict => return Err((0, format!("Unrecognized keyword: {}", keyword))),
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    // resolves dotted attribute codes ("241.1", "241.26.10415.1", "26.10415.1") into parent and vendor
+    fn parse_attribute(&self, parts: &[&str], block: &Option<VendorBlock>) -> Result<RadiusAttributeDef, RowError> {
+        let row = parts.join(" ");
+        let path: Vec<&str> = parts.get(2).ok_or_else(|| (0, format!("Malformed attribute row: {}", row)))?.split('.').collect();
+        let (mut parent, mut vendor) = match block {
+            Some((vendor, parent)) => (parent.clone(), Some(vendor.clone())),
+            None => (None, None),
+        };
+        let parse_vendor = |id: &str| id.parse::<u32>().ok()
+            .and_then(|id| self.get_vendor_by_id(id))
+            .cloned()
+            .ok_or_else(|| (2, format!("Unrecognized vendor id: {}", id)));
+
+        let mut i = 0;
+        if vendor.is_none() && path.len() > 2 && path[0] == VENDOR_SPECIFIC.to_string() {
+            vendor = Some(parse_vendor(path[1])?);
+            i = 2;
+        }
+        while i + 1 < path.len() {
+            let code = path[i].parse::<u8>().map_err(|e| (2, format!("Malformed attriute code: {:?}", e)))?;
+            let def = self.find_attribute(parent.as_ref(), vendor.as_ref().map(|v| v.get_id()), code)
+                .ok_or_else(|| (2, format!("Unrecognized parent attribute {} in: {}", path[i], row)))?
+                .clone();
+            i += 1;
+            if *def.get_type() == RadiusAttributeType::Evs {
+                if i + 1 >= path.len() {
+                    return Err((2, format!("Missing vendor in: {}", row)));
+                }
+                vendor = Some(parse_vendor(path[i])?);
+                i += 1;
+            }
+            parent = Some(def);
+        }
+
+        let mut own = parts.to_vec();
+        own[2] = path[path.len() - 1];
+        let mut def = RadiusAttributeDef::from_str(&own.join(" ")).map_err(|e| {
+            let field = if parts.len() != 4 && parts.len() != 5 {
+                0
+            }
+            else if own[2].parse::<u8>().is_err() {
+                2
+            }
+            else if RadiusAttributeType::from_str(parts[3]).is_err() {
+                3
+            }
+            else {
+                4
+            };
+            (field, e)
+        })?;
+        if let Some(vendor) = vendor {
+            def.set_vendor(vendor);
+        }
+        if let Some(parent) = parent {
+            match parent.get_type() {
+                RadiusAttributeType::Extended | RadiusAttributeType::LongExtended => {},
+                RadiusAttributeType::Evs if def.get_vendor().is_some() => {},
+                RadiusAttributeType::Tlv => {},
+                _ => return Err((2, format!("Attribute {} cannot contain {}", parent.get_name(), def.get_name()))),
+            }
+            def.set_parent(parent);
+        }
+
+        Ok(def)
+    }
+
+    // decodes the nested attributes of a tlv attribute
+    fn load_tlv(&self, attr: &mut RadiusAttribute) -> Result<(), String> {
+        if *attr.get_definition().get_type() != RadiusAttributeType::Tlv {
+            return Ok(());
+        }
+
+        let value = attr.get_value().to_vec();
+        let vendor = attr.get_definition().get_vendor().map(|v| v.get_id());
+        let mut i = 0;
+        while i + 2 <= value.len() {
+            let size = value[i + 1] as usize;
+            if size < 2 || i + size > value.len() {
+                return Err(format!("Malformed {} attribute", attr.get_definition().get_name()));
+            }
+            let def = match self.find_attribute(Some(attr.get_definition()), vendor, value[i]) {
+                Some(def) => def.clone(),
+                None => RadiusAttributeDef::unknown(value[i], attr.get_definition().get_vendor().cloned(), Some(attr.get_definition().clone())),
+            };
+            for mut child in RadiusAttribute::from_wire(def, &value[(i + 2)..(i + size)]) {
+                child.set_container(i);
+                self.load_tlv(&mut child)?;
+                attr.add_child(child)?;
+            }
             i += size;
         }
+        if i != value.len() {
+            return Err(format!("Malformed {} attribute", attr.get_definition().get_name()));
+        }
+
+        Ok(())
+    }
+
+    // decodes the value of an extended attribute, given its extended type
+    fn load_extended(&self, parent: &RadiusAttributeDef, ext: &[u8], value: &[u8]) -> Result<Vec<RadiusAttribute>, String> {
+        let code = *ext.first().ok_or_else(|| format!("Malformed {} attribute", parent.get_name()))?;
+        let def = match self.find_attribute(Some(parent), None, code) {
+            Some(def) => def,
+            None => return Ok(vec![RadiusAttribute::new(RadiusAttributeDef::unknown(code, None, Some(parent.clone())), value.to_vec())]),
+        };
+        if *def.get_type() != RadiusAttributeType::Evs {
+            return Ok(RadiusAttribute::from_wire(def.clone(), value));
+        }
+
+        let vendor = if value.len() >= 5 {
+            u32::from_be_bytes([value[0], value[1], value[2], value[3]])
+        }
+        else {
+            return Err(format!("Malformed {} attribute", def.get_name()));
+        };
+        match (self.find_attribute(Some(def), Some(vendor), value[4]), self.get_vendor_by_id(vendor)) {
+            (Some(vsa), _) => Ok(RadiusAttribute::from_wire(vsa.clone(), &value[5..])),
+            (None, Some(v)) => Ok(vec![RadiusAttribute::new(RadiusAttributeDef::unknown(value[4], Some(v.clone()), Some(def.clone())), value[5..].to_vec())]),
+            // unknown vendors are kept as an opaque Extended-Vendor-Specific attribute
+            (None, None) => Ok(vec![RadiusAttribute::new(def.clone(), value.to_vec())]),
+        }
+    }
+
+    fn load_vendor_specific(&self, value: &[u8]) -> Result<Vec<RadiusAttribute>, String> {
+        let vendor = if value.len() >= 4 {
+            self.get_vendor_by_id(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+        }
+        else {
+            None
+        };
 
-        Ok(RadiusData::new(RadiusCode::from_u8(data[0])?, Some(data[1]), Some(data[4..20].to_vec()), Some(attributes)))
+        match vendor {
+            Some(vendor) => {
+                let mut res = Vec::new();
+                for (code, v) in vendor.decode(&value[4..])? {
+                    match self.find_attribute(None, Some(vendor.get_id()), code) {
+                        Some(def) => res.append(&mut RadiusAttribute::from_wire(def.clone(), &v)),
+                        None => res.push(RadiusAttribute::new(RadiusAttributeDef::unknown(code, Some(vendor.clone()), None), v)),
+                    }
+                }
+                Ok(res)
+            },
+            // unknown vendors are kept as an opaque Vendor-Specific attribute
+            None => Ok(vec![self.create_attribute_by_code(VENDOR_SPECIFIC, value.to_vec())
+                .unwrap_or_else(|_| RadiusAttribute::new(RadiusAttributeDef::unknown(VENDOR_SPECIFIC, None, None), value.to_vec()))]),
+        }
     }
 }
 
 impl FromStr for Dictionary {
-    type Err = String;
+    type Err = DictionaryError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut res = Dictionary {
-            attributes: Vec::new(),
-        };
+        let mut res = Dictionary::empty();
+        res.parse(s, None, &mut ParseContext::new(false))?;
+        Ok(res)
+    }
+}
+
+impl fmt::Display for Dictionary {
+    /// canonical FreeRADIUS dictionary text, readable back by `Dictionary::from_str`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for vendor in self.vendors.iter() {
+            writeln!(f, "{}", vendor)?;
+        }
+
+        // vendor attributes are grouped in BEGIN-VENDOR blocks, with codes relative to the block
+        let mut block: Option<(&RadiusVendorDef, Option<&RadiusAttributeDef>)> = None;
+        for def in self.attributes.iter() {
+            let current = Self::vendor_block(def);
+            let key = |b: Option<(&RadiusVendorDef, Option<&RadiusAttributeDef>)>| b.map(|(v, evs)| (v.get_id(), evs.map(|e| e.get_oid())));
+            if key(current) != key(block) {
+                if let Some((vendor, _)) = block {
+                    writeln!(f, "END-VENDOR\t{}", vendor.get_name())?;
+                }
+                match current {
+                    Some((vendor, Some(evs))) => writeln!(f, "BEGIN-VENDOR\t{}\tformat={}", vendor.get_name(), evs.get_name())?,
+                    Some((vendor, None)) => writeln!(f, "BEGIN-VENDOR\t{}", vendor.get_name())?,
+                    None => {},
+                }
+                block = current;
+            }
 
-        for l in s.lines() {
-            if l.starts_with("ATTRIBUTE") {
-                res.attributes.push(RadiusAttributeDef::from_str(l)?);
+            let oid = def.get_oid();
+            let prefix = match current {
+                Some((vendor, Some(evs))) => format!("{}.{}.", evs.get_oid(), vendor.get_id()),
+                Some((vendor, None)) => format!("{}.{}.", VENDOR_SPECIFIC, vendor.get_id()),
+                None => String::new(),
+            };
+            write!(f, "ATTRIBUTE\t{}\t{}\t{}", def.get_name(), oid.strip_prefix(&prefix).unwrap_or(&oid), def.get_type().get_name())?;
+            if !def.get_flags().is_empty() {
+                write!(f, "\t{}", def.get_flags())?;
             }
+            writeln!(f)?;
+        }
+        if let Some((vendor, _)) = block {
+            writeln!(f, "END-VENDOR\t{}", vendor.get_name())?;
         }
 
-        Ok(res)
+        for value in self.sorted_values() {
+            writeln!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Dictionary {
+    /// IETF attributes of DEFAULT_DICTIONARY
+    fn default() -> Dictionary {
+        Dictionary::from_str(DEFAULT_DICTIONARY).expect("default dictionary")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Dictionary, DEFAULT_DICTIONARY};
+    use super::{Dictionary, Precedence, RadiusAttribute, RadiusAttributeDef, RadiusAttributeType, RadiusCode, RadiusData, DEFAULT_DICTIONARY};
+    use super::super::super::radius::RadiusValue;
+    use std::net::Ipv4Addr;
+    use std::path::Path;
     use std::str::FromStr;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    // Access-Accept packet with identifier 1 and a zero authenticator
+    fn packet(attributes: &[u8]) -> Vec<u8> {
+        let mut res = vec![2, 1];
+        res.extend_from_slice(&(20 + attributes.len() as u16).to_be_bytes());
+        res.extend_from_slice(&[0; 16]);
+        res.extend_from_slice(attributes);
+        res
+    }
 
     #[test]
     fn to_bytes() {
@@ -176,6 +1842,778 @@ mod tests {
         ];
 
         let data = d.load_bytes(&example).unwrap();
-        assert_eq!(example.to_vec(), data.get_bytes());
+        assert_eq!(example.to_vec(), data.get_bytes().unwrap());
+    }
+
+    #[test]
+    fn typed_values() {
+        let d = Dictionary::default();
+        let mut data = RadiusData::new(RadiusCode::AccessAccept, None, None, None);
+        data.add_attribute(d.create_attribute_by_name("User-Name", b"testing".to_vec()).unwrap());
+        data.add_attribute(d.create_attribute_by_name("Framed-IP-Address", vec![192, 168, 0, 1]).unwrap());
+        data.add_attribute(d.create_attribute_by_name("Session-Timeout", vec![0, 0, 0x0e, 0x10]).unwrap());
+        data.add_attribute(d.create_attribute_by_name("NAS-Port", vec![0]).unwrap());
+
+        assert_eq!(data.get_string("User-Name").unwrap(), "testing");
+        assert_eq!(data.get_ipv4("Framed-IP-Address").unwrap(), Ipv4Addr::new(192, 168, 0, 1));
+        assert_eq!(data.get_u32("Session-Timeout").unwrap(), 3600);
+        assert_eq!(data.get_typed_value("Session-Timeout").unwrap(), RadiusValue::U32(3600));
+        assert_eq!(data.get_u32("NAS-Port").err().unwrap(), "NAS-Port: Invalid length for integer: 1 bytes, expected 4");
+        assert_eq!(data.get_u32("User-Name").err().unwrap(), "User-Name: expected integer value, attribute type is string");
+        assert_eq!(data.get_u32("Idle-Timeout").err().unwrap(), "Attribute not found: Idle-Timeout");
+    }
+
+    #[test]
+    fn native_values() {
+        let d = Dictionary::default();
+
+        assert_eq!(d.create_attribute("NAS-Port", 0u32).unwrap().get_value(), [0, 0, 0, 0]);
+        assert_eq!(d.create_attribute("NAS-IP-Address", Ipv4Addr::new(172, 25, 0, 2)).unwrap().get_value(), [172, 25, 0, 2]);
+        assert_eq!(d.create_attribute("User-Name", "testing").unwrap().get_value(), b"testing");
+        assert_eq!(d.create_attribute("Event-Timestamp", UNIX_EPOCH + Duration::from_secs(258)).unwrap().get_value(), [0, 0, 1, 2]);
+        assert_eq!(d.create_attribute("Framed-IP-Address", "10.0.0.1").unwrap().get_value(), [10, 0, 0, 1]);
+        assert_eq!(d.create_attribute("EAP-Message", vec![0; 300]).unwrap().get_value().len(), 300);
+
+        assert_eq!(d.create_attribute("NAS-Port", vec![0]).err().unwrap(), "NAS-Port: Invalid length for integer: 1 bytes, expected 4");
+        assert_eq!(d.create_attribute("NAS-Port", Ipv4Addr::LOCALHOST).err().unwrap(), "NAS-Port: Cannot convert ipaddr into integer");
+        assert_eq!(d.create_attribute("Class", vec![0; 254]).err().unwrap(), "Class: value too long, 254 bytes exceeding 253");
+        assert!(d.create_attribute("Unknown-Attr", 0u32).is_err());
+    }
+
+    #[test]
+    fn text() {
+        let d = Dictionary::default();
+        let text = "User-Name = \"test \\\"quoted\\\"\"\nService-Type = Framed-User\nFramed-IP-Address = 10.0.0.1\nClass = 0x0102\nTunnel-Type:1 = VLAN\nSession-Timeout = 3600\n";
+
+        let mut data = RadiusData::new(RadiusCode::AccessAccept, None, None, None);
+        for a in d.parse_attributes(text).unwrap() {
+            data.add_attribute(a);
+        }
+        assert_eq!(data.get_string("User-Name").unwrap(), "test \"quoted\"");
+        assert_eq!(data.get_u32("Service-Type").unwrap(), 2);
+        assert_eq!(data.get_attribute_by_name("Class").unwrap().get_value(), [1, 2]);
+        assert_eq!(data.get_attribute_by_name("Tunnel-Type").unwrap().get_tag(), Some(1));
+        assert_eq!(d.format_data(&data), text);
+        assert!(data.to_string().contains("Service-Type = 2\n"));
+
+        let attrs = d.parse_attributes("# comment\nUser-Name = \"a, b\", NAS-Port = 0x00000001").unwrap();
+        assert_eq!((attrs.len(), attrs[0].get_value(), attrs[1].get_value()), (2, &b"a, b"[..], &[0, 0, 0, 1][..]));
+
+        assert_eq!(d.parse_attributes("User-Name").err().unwrap(), "Malformed attribute: User-Name");
+        assert_eq!(d.parse_attributes("User-Name = \"a").err().unwrap(), "Unterminated quoted value: User-Name = \"a");
+        assert_eq!(d.parse_attributes("NAS-Port = 0x00").err().unwrap(), "NAS-Port: Invalid length for integer: 1 bytes, expected 4");
+        assert_eq!(d.parse_attributes("User-Name:1 = \"a\"").err().unwrap(), "Attribute User-Name has no tag: User-Name:1 = \"a\"");
+        assert!(d.parse_attributes("Service-Type = Unknown-Value").is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        let d = Dictionary::default();
+        let error = |data: &[u8]| d.load_bytes(data).err().map(|e| (e.get_offset(), e.get_message().to_owned()));
+        let with_length = |mut data: Vec<u8>, len: u16| {
+            data[2..4].copy_from_slice(&len.to_be_bytes());
+            data
+        };
+
+        assert_eq!(error(&[2, 1, 0]), Some((0, "Packet too short: 3 bytes".to_owned())));
+        assert_eq!(error(&with_length(packet(&[]), 21)), Some((2, "Invalid packet length: 21, 20 bytes received".to_owned())));
+        assert_eq!(error(&with_length(packet(&[]), 19)), Some((2, "Invalid packet length: 19, 20 bytes received".to_owned())));
+        assert_eq!(error(&packet(&[1])), Some((20, "Truncated attribute header".to_owned())));
+        // zero length attribute used to loop forever
+        assert_eq!(error(&packet(&[1, 0])), Some((21, "Invalid attribute length: 0".to_owned())));
+        assert_eq!(error(&packet(&[1, 9, 0x61, 0x62, 0x63])), Some((21, "Invalid attribute length: 9".to_owned())));
+        assert_eq!(error(&packet(&[1, 3, 0x61, 241, 2, 245, 3, 1])), Some((23, "Malformed Extended-Attribute-1 attribute".to_owned())));
+
+        let d = Dictionary::from_str("ATTRIBUTE User-Name 1 string\nVENDOR Test 10415\nBEGIN-VENDOR Test\nATTRIBUTE Test-Attr 1 string\nEND-VENDOR Test\n").unwrap();
+        assert_eq!(d.load_bytes(&packet(&[1, 3, 0x61, 26, 7, 0, 0, 0x28, 0xaf, 1])).err().unwrap().to_string(), "offset 23: Truncated Test vendor attribute");
+
+        // padding beyond the header length is ignored
+        let data = with_length(packet(&[1, 3, 0x61, 0xff, 0xff]), 23);
+        assert_eq!(d.load_bytes(&data).unwrap().get_string("User-Name").unwrap(), "a");
+    }
+
+    #[test]
+    fn unknown_attributes() {
+        let mut d = Dictionary::default();
+        d.extend_from_str("VENDOR Test 99\nBEGIN-VENDOR Test\nATTRIBUTE Test-Attr 1 string\nEND-VENDOR Test\n").unwrap();
+
+        let example = packet(&[
+            0xe6, 0x04, 0x01, 0x02,
+            0x1a, 0x09, 0x00, 0x00, 0x00, 0x63, 0x02, 0x03, 0x61,
+            0x1a, 0x09, 0x00, 0x00, 0x30, 0x39, 0x01, 0x03, 0x61,
+            0xf1, 0x04, 0xc8, 0x05,
+            0xad, 0x0a, 0x01, 0x06, 0x00, 0x00, 0x00, 0x08, 0x09, 0x02,
+            0xf1, 0x09, 0x1a, 0x00, 0x00, 0x00, 0x63, 0x07, 0x01,
+        ]);
+
+        let data = d.load_bytes(&example).unwrap();
+        assert_eq!(example, data.get_bytes().unwrap());
+        assert_eq!(d.format_data(&data), concat!(
+            "Attr-230 = 0x0102\n",
+            "Attr-26.99.2 = 0x61\n",
+            "Vendor-Specific = 0x00003039010361\n",
+            "Attr-241.200 = 0x05\n",
+            "IPv6-6rd-IPv4MaskLen = 8, Attr-173.9 = 0x\n",
+            "Attr-241.26.99.7 = 0x01\n",
+        ));
+        assert!(data.get_attribute_by_name("Attr-230").unwrap().get_definition().is_unknown());
+        assert!(!data.get_attribute_by_name("Vendor-Specific").unwrap().get_definition().is_unknown());
+
+        // listings of unknown attributes are read back by their identifiers
+        let mut back = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(vec![0; 16]), None);
+        for a in d.parse_attributes(&d.format_data(&data)).unwrap() {
+            back.add_nested_attribute(a).unwrap();
+        }
+        assert_eq!(back.get_bytes().unwrap(), example);
+        assert!(d.create_attribute_by_name("Attr-26.12345.1", vec![]).is_err());
+        assert!(d.create_attribute_by_name("Attr-256", vec![]).is_err());
+
+        // unknown sub-attributes of a single Vendor-Specific attribute stay together
+        d.extend_from_str("VENDOR Microsoft 311\n").unwrap();
+        let example = packet(&[
+            0x1a, 0x10, 0x00, 0x00, 0x01, 0x37, 0x07, 0x06, 0x00, 0x00, 0x00, 0x01, 0x08, 0x04, 0x00, 0x02,
+            0x1a, 0x0a, 0x00, 0x00, 0x01, 0x37, 0x07, 0x04, 0x00, 0x03,
+        ]);
+        let data = d.load_bytes(&example).unwrap();
+        assert_eq!(d.format_data(&data), "Attr-26.311.7 = 0x00000001\nAttr-26.311.8 = 0x0002\nAttr-26.311.7 = 0x0003\n");
+        assert_eq!(data.get_bytes().unwrap(), example);
+    }
+
+    #[test]
+    fn vendor_specific() {
+        let d = Dictionary::from_str(r"
+VENDOR      3GPP        10415
+BEGIN-VENDOR    3GPP
+ATTRIBUTE   3GPP-IMSI       1   string
+ATTRIBUTE   3GPP-Charging-ID    2   integer
+END-VENDOR      3GPP
+").unwrap();
+
+        let a = d.create_attribute_by_name("3GPP-IMSI", b"001010123456789".to_vec()).unwrap();
+        assert_eq!(a.get_definition().get_vendor().unwrap().get_id(), 10415);
+        assert_eq!(a.get_bytes().unwrap(), [
+            0x1a, 0x17, 0x00, 0x00, 0x28, 0xaf, 0x01, 0x11,
+            0x30, 0x30, 0x31, 0x30, 0x31, 0x30, 0x31, 0x32,
+            0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39
+        ]);
+
+        let mut attributes = a.get_bytes().unwrap();
+        attributes.extend_from_slice(&[0x1a, 0x0c, 0x00, 0x00, 0x28, 0xaf, 0x02, 0x06, 0x00, 0x00, 0x00, 0x2a]);
+        let example = packet(&attributes);
+
+        let data = d.load_bytes(&example).unwrap();
+        assert_eq!(data.get_attribute_by_name("3GPP-IMSI").unwrap().get_value(), b"001010123456789");
+        assert_eq!(data.get_attribute_by_name("3GPP-Charging-ID").unwrap().get_value(), [0, 0, 0, 42]);
+        assert!(data.get_attribute_by_code(1).is_none());
+        assert_eq!(example, data.get_bytes().unwrap());
+
+        // several vendor attributes in a single Vendor-Specific are encoded back the same way
+        let example = packet(&[
+            0x1a, 0x0f, 0x00, 0x00, 0x28, 0xaf, 0x01, 0x03, 0x30, 0x02, 0x06, 0x00, 0x00, 0x00, 0x2a,
+        ]);
+        let data = d.load_bytes(&example).unwrap();
+        assert_eq!(data.get_attributes().len(), 2);
+        assert_eq!(example, data.get_bytes().unwrap());
+        // built ones get a Vendor-Specific each
+        let built = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(vec![0; 16]), Some(data.get_attributes().iter()
+            .map(|a| RadiusAttribute::new(a.get_definition().clone(), a.get_value().to_vec())).collect()));
+        assert_eq!(built.get_bytes().unwrap().len(), example.len() + 6);
+        // and so do grouped ones not fitting together anymore
+        let mut data = data;
+        data.get_attribute_by_name_mut("3GPP-IMSI").unwrap().set_value(vec![0x30; 245]);
+        let bytes = data.get_bytes().unwrap();
+        assert_eq!(bytes.len(), 20 + (8 + 245) + 12);
+        assert_eq!(bytes[20..22], [0x1a, 0xfd]);
+    }
+
+    #[test]
+    fn values() {
+        let d = Dictionary::from_str(DEFAULT_DICTIONARY).unwrap();
+
+        assert_eq!(d.get_value("Service-Type", "Framed-User"), Some(2));
+        assert_eq!(d.get_value_name("Acct-Status-Type", 1), Some("Start"));
+        assert_eq!(d.get_value("Service-Type", "Start"), None);
+
+        let a = d.create_attribute_by_value("Acct-Status-Type", "Interim-Update").unwrap();
+        assert_eq!(a.get_value(), [0, 0, 0, 3]);
+        assert_eq!(d.get_attribute_value_name(&a), Some("Interim-Update"));
+        assert!(d.create_attribute_by_value("Acct-Status-Type", "Unknown").is_err());
+    }
+
+    #[test]
+    fn flags() {
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   Tunnel-Type     64  integer has_tag
+ATTRIBUTE   EAP-Message     79  octets  concat
+ATTRIBUTE   Tunnel-Private-Group-Id 81  string  has_tag
+ATTRIBUTE   Test-Array      240 ipaddr  array
+VALUE   Tunnel-Type     VLAN    13
+").unwrap();
+        assert!(Dictionary::from_str("ATTRIBUTE Test-Array 240 string array").is_err());
+        assert!(Dictionary::from_str("ATTRIBUTE Test-Flag 240 string unknown").is_err());
+
+        let mut attributes = vec![
+            0x40, 0x06, 0x01, 0x00, 0x00, 0x0d,
+            0x51, 0x06, 0x01, 0x31, 0x30, 0x30,
+            0xf0, 0x0a, 0x0a, 0x00, 0x00, 0x01, 0x0a, 0x00, 0x00, 0x02,
+            0xf0, 0x06, 0x0a, 0x00, 0x00, 0x03,
+        ];
+        let eap = vec![0xaa; 300];
+        attributes.extend_from_slice(&[0x4f, 0xff]);
+        attributes.extend_from_slice(&eap[..253]);
+        attributes.extend_from_slice(&[0x4f, 0x31]);
+        attributes.extend_from_slice(&eap[253..]);
+        let example = packet(&attributes);
+
+        let data = d.load_bytes(&example).unwrap();
+
+        let tunnel_type = data.get_attribute_by_name("Tunnel-Type").unwrap();
+        assert_eq!(tunnel_type.get_tag(), Some(1));
+        assert_eq!(tunnel_type.get_value(), [0, 0, 0, 13]);
+        assert_eq!(d.get_attribute_value_name(tunnel_type), Some("VLAN"));
+
+        let vlan = data.get_attribute_by_name("Tunnel-Private-Group-Id").unwrap();
+        assert_eq!(vlan.get_tag(), Some(1));
+        assert_eq!(vlan.get_value(), b"100");
+
+        assert_eq!(data.get_attribute_by_name("EAP-Message").unwrap().get_value(), eap.as_slice());
+        assert_eq!(data.get_attributes_by_name("EAP-Message").count(), 1);
+        assert_eq!(data.get_attribute_by_name("Test-Array").unwrap().get_value(), [10, 0, 0, 1]);
+        // array values are packed back in the attribute they were received in
+        assert_eq!(data.get_attributes_by_name("Test-Array").count(), 3);
+
+        assert_eq!(example, data.get_bytes().unwrap());
+    }
+
+    #[test]
+    fn tags() {
+        let d = Dictionary::default();
+        let mut data = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(vec![0; 16]), None);
+        data.add_attribute(d.create_tagged_attribute("Tunnel-Type", 1, 13u32).unwrap());
+        data.add_attribute(d.create_tagged_attribute("Tunnel-Medium-Type", 1, 6u32).unwrap());
+        data.add_attribute(d.create_tagged_attribute("Tunnel-Type", 2, 3u32).unwrap());
+        data.add_attribute(d.create_tagged_attribute("Tunnel-Private-Group-Id", 1, "100").unwrap());
+        data.add_attribute(d.create_attribute("Tunnel-Private-Group-Id", "\u{1}x").unwrap());
+
+        let bytes = data.get_bytes().unwrap();
+        assert_eq!(bytes[20..38], [0x40, 0x06, 0x01, 0x00, 0x00, 0x0d, 0x41, 0x06, 0x01, 0x00, 0x00, 0x06, 0x40, 0x06, 0x02, 0x00, 0x00, 0x03]);
+        assert_eq!(bytes[38..], [0x51, 0x06, 0x01, 0x31, 0x30, 0x30, 0x51, 0x05, 0x00, 0x01, 0x78]);
+
+        let decoded = d.load_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get_attribute_by_name_and_tag("Tunnel-Type", 2).unwrap().get_u32().unwrap(), 3);
+        assert_eq!(decoded.get_attributes_by_tag(1).len(), 3);
+        let groups: Vec<(u8, Vec<&str>)> = decoded.get_tag_groups().into_iter()
+            .map(|(tag, attrs)| (tag, attrs.iter().map(|a| a.get_definition().get_name()).collect()))
+            .collect();
+        assert_eq!(groups, [
+            (1, vec!["Tunnel-Type", "Tunnel-Medium-Type", "Tunnel-Private-Group-Id"]),
+            (2, vec!["Tunnel-Type"]),
+            (0, vec!["Tunnel-Private-Group-Id"]),
+        ]);
+        assert_eq!(decoded.get_bytes().unwrap(), bytes);
+
+        assert_eq!(d.create_tagged_attribute("User-Name", 1, "a").err().unwrap(), "Attribute User-Name has no tag");
+        assert_eq!(d.create_tagged_attribute("Tunnel-Type", 0x20, 1u32).err().unwrap(), "Invalid tag for Tunnel-Type: 32");
+    }
+
+    #[test]
+    fn long_values() {
+        let d = Dictionary::default();
+        let eap: Vec<u8> = (0..600).map(|n| n as u8).collect();
+
+        let mut data = RadiusData::new(RadiusCode::AccessRequest, Some(1), Some(vec![0; 16]), None);
+        data.add_attribute(d.create_attribute("EAP-Message", eap.clone()).unwrap());
+        data.add_attribute(d.create_attribute("EAP-Message", vec![1, 2]).unwrap());
+        data.add_attribute(d.create_attribute("State", vec![3]).unwrap());
+        let bytes = data.get_bytes().unwrap();
+        assert_eq!(bytes.len(), 20 + (2 + 253) * 2 + (2 + 94) + (2 + 2) + (2 + 1));
+        assert_eq!(bytes[20..22], [79, 255]);
+        assert_eq!(bytes[275..277], [79, 255]);
+        assert_eq!(bytes[530..532], [79, 96]);
+
+        // consecutive fragments are reassembled, and encoded back as received whatever the split
+        let mut attributes = vec![79, 12];
+        attributes.extend_from_slice(&eap[..10]);
+        attributes.extend_from_slice(&[79, 7]);
+        attributes.extend_from_slice(&eap[10..15]);
+        attributes.extend_from_slice(&[24, 3, 3]);
+        attributes.extend_from_slice(&[79, 3, 15]);
+        let example = packet(&attributes);
+        let split = d.load_bytes(&example).unwrap();
+        let messages: Vec<&[u8]> = split.get_attributes_by_name("EAP-Message").map(|a| a.get_value()).collect();
+        assert_eq!(messages, [&eap[..15], &[15][..]]);
+        assert_eq!(split.get_bytes().unwrap(), example);
+
+        let decoded = d.load_bytes(&bytes).unwrap();
+        let mut joined = eap.clone();
+        joined.extend_from_slice(&[1, 2]);
+        assert_eq!(decoded.get_attributes_by_name("EAP-Message").count(), 1);
+        assert_eq!(decoded.get_attribute_by_name("EAP-Message").unwrap().get_value(), joined.as_slice());
+        assert_eq!(decoded.get_typed_value("EAP-Message").unwrap(), RadiusValue::Bytes(joined));
+        assert_eq!(decoded.get_bytes().unwrap(), bytes);
+
+        // modified values are split again
+        let mut modified = decoded.clone();
+        modified.get_attribute_by_name_mut("EAP-Message").unwrap().set_value(eap[..300].to_vec());
+        assert_eq!(modified.get_bytes().unwrap()[20..24], [79, 255, 0, 1]);
+        assert_eq!(modified.get_bytes().unwrap()[275..277], [79, 49]);
+
+        // typed access is not bound by the single attribute limit
+        let long = d.create_attribute("EAP-Message", eap.clone()).unwrap();
+        assert_eq!(long.get_typed_value().unwrap(), RadiusValue::Bytes(eap.clone()));
+
+        let mut data = RadiusData::new(RadiusCode::AccessRequest, Some(1), Some(vec![0; 16]), None);
+        data.add_attribute(d.create_attribute_by_name("Class", vec![0; 254]).unwrap());
+        assert_eq!(data.get_bytes().err().unwrap(), "Value too long for Class: 254 bytes, maximum 253");
+
+        let mut data = RadiusData::new(RadiusCode::AccessRequest, Some(1), Some(vec![0; 16]), None);
+        data.add_attribute(d.create_attribute("EAP-Message", vec![0; 4096]).unwrap());
+        assert_eq!(data.get_bytes().err().unwrap(), "Packet too long: 4150 bytes, maximum 4096");
+
+        // reassembled long extended values are checked against their type
+        let d = Dictionary::from_str("ATTRIBUTE Extended-Attribute-5 245 long-extended\nATTRIBUTE Test-Long-Int 245.1 integer").unwrap();
+        let example = packet(&[0xf5, 0x08, 0x01, 0x80, 0, 0, 0, 1, 0xf5, 0x08, 0x01, 0x00, 0, 0, 0, 2]);
+        let err = d.load_bytes(&example).err().unwrap();
+        assert_eq!((err.get_offset(), err.get_message()), (20, "Value too long for Test-Long-Int: 8 bytes, maximum 4"));
+    }
+
+    #[test]
+    fn multiple_values() {
+        let d = Dictionary::default();
+        let attrs = d.parse_attributes("Proxy-State = 0x01, Class = \"a\", Proxy-State = 0x02, Reply-Message = \"one\", Class = \"b\", Proxy-State = 0x03").unwrap();
+        let mut data = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(vec![0; 16]), Some(attrs));
+
+        let states: Vec<&[u8]> = data.get_attributes_by_name("Proxy-State").map(|a| a.get_value()).collect();
+        assert_eq!(states, [[1], [2], [3]]);
+        assert_eq!(data.get_attributes_by_code(25).count(), 2);
+
+        // order is preserved through the wire
+        let decoded = d.load_bytes(&data.get_bytes().unwrap()).unwrap();
+        assert_eq!(d.format_data(&decoded), d.format_data(&data));
+        assert_eq!(decoded.get_bytes().unwrap(), data.get_bytes().unwrap());
+
+        let removed = data.replace_attribute(d.create_attribute("Class", "c").unwrap());
+        assert_eq!(removed.len(), 2);
+        data.insert_attribute(0, d.create_attribute("Reply-Message", "zero").unwrap()).unwrap();
+        assert!(data.insert_attribute(9, d.create_attribute("Reply-Message", "nine").unwrap()).is_err());
+        for a in data.get_attributes_by_name_mut("Proxy-State") {
+            a.set_value(vec![0]);
+        }
+        assert_eq!(data.remove_attribute(1).unwrap().get_value(), [0]);
+        data.retain_attributes(|a| a.get_value() != b"one");
+        assert_eq!(d.format_data(&data), "Reply-Message = \"zero\"\nClass = 0x63\nProxy-State = 0x00\nProxy-State = 0x00\n");
+        assert_eq!(data.remove_attributes_by_name("Proxy-State").len(), 2);
+        assert!(data.get_attributes_by_name("Proxy-State").next().is_none());
+    }
+
+    #[test]
+    fn extended() {
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   User-Name           1       string
+ATTRIBUTE   Extended-Attribute-1    241     extended
+ATTRIBUTE   Frag-Status         241.1   integer
+ATTRIBUTE   Extended-Vendor-Specific-1  241.26  evs
+ATTRIBUTE   Extended-Attribute-5    245     long-extended
+ATTRIBUTE   Test-Long           245.1   octets
+ATTRIBUTE   Extended-Vendor-Specific-5  245.26  evs
+VALUE   Frag-Status     More-Data-Request   3
+VENDOR      Test        32473
+BEGIN-VENDOR    Test    format=Extended-Vendor-Specific-1
+ATTRIBUTE   Test-Ext-Vendor     1   integer
+END-VENDOR      Test
+ATTRIBUTE   Test-Ext-Vendor-Long    245.26.32473.2  octets
+").unwrap();
+        assert!(Dictionary::from_str("ATTRIBUTE User-Name 1 string\nATTRIBUTE Test 1.1 string").is_err());
+
+        let frag = d.create_attribute_by_value("Frag-Status", "More-Data-Request").unwrap();
+        assert_eq!(frag.get_definition().get_oid(), "241.1");
+        assert_eq!(frag.get_bytes().unwrap(), [0xf1, 0x07, 0x01, 0x00, 0x00, 0x00, 0x03]);
+
+        let evs = d.create_attribute_by_name("Test-Ext-Vendor", vec![0, 0, 0, 1]).unwrap();
+        assert_eq!(evs.get_definition().get_oid(), "241.26.32473.1");
+        assert_eq!(evs.get_bytes().unwrap(), [0xf1, 0x0c, 0x1a, 0x00, 0x00, 0x7e, 0xd9, 0x01, 0x00, 0x00, 0x00, 0x01]);
+
+        let long = d.create_attribute_by_name("Test-Long", vec![0x55; 300]).unwrap();
+        let bytes = long.get_bytes().unwrap();
+        assert_eq!(bytes.len(), 4 + 251 + 4 + 49);
+        assert_eq!(&bytes[..4], [0xf5, 0xff, 0x01, 0x80]);
+        assert_eq!(&bytes[255..259], [0xf5, 0x35, 0x01, 0x00]);
+
+        let long_evs = d.create_attribute_by_oid("245.26.32473.2", vec![0x66; 260]).unwrap();
+
+        let mut attributes = frag.get_bytes().unwrap();
+        attributes.append(&mut evs.get_bytes().unwrap());
+        attributes.append(&mut long.get_bytes().unwrap());
+        attributes.append(&mut long_evs.get_bytes().unwrap());
+        let example = packet(&attributes);
+
+        let data = d.load_bytes(&example).unwrap();
+        assert_eq!(data.get_attribute_by_name("Frag-Status").unwrap().get_value(), [0, 0, 0, 3]);
+        assert_eq!(data.get_attribute_by_name("Test-Ext-Vendor").unwrap().get_value(), [0, 0, 0, 1]);
+        assert_eq!(data.get_attribute_by_oid("245.1").unwrap().get_value(), vec![0x55; 300].as_slice());
+        assert_eq!(data.get_attribute_by_name("Test-Ext-Vendor-Long").unwrap().get_value(), vec![0x66; 260].as_slice());
+        assert_eq!(example, data.get_bytes().unwrap());
+
+        // fragments shorter than allowed are encoded back as received
+        let mut attributes = vec![0xf5, 0x0e, 0x01, 0x80];
+        attributes.extend_from_slice(&[0x55; 10]);
+        attributes.extend_from_slice(&[0xf5, 0x09, 0x01, 0x00]);
+        attributes.extend_from_slice(&[0x55; 5]);
+        let example = packet(&attributes);
+        let data = d.load_bytes(&example).unwrap();
+        assert_eq!(data.get_attribute_by_name("Test-Long").unwrap().get_value(), [0x55; 15]);
+        assert_eq!(example, data.get_bytes().unwrap());
+    }
+
+    #[test]
+    fn tlv() {
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   User-Name       1   string
+VENDOR      WiMAX       24757   format=1,1,c
+ATTRIBUTE   WiMAX-Capability    26.24757.1      tlv
+ATTRIBUTE   WiMAX-Release       26.24757.1.1    string
+ATTRIBUTE   WiMAX-Accounting-Capabilities   26.24757.1.2    byte
+BEGIN-VENDOR    WiMAX
+ATTRIBUTE   WiMAX-Hotlining-Capabilities    1.3     tlv
+ATTRIBUTE   WiMAX-Hotline-Profile-Id        1.3.1   integer
+END-VENDOR      WiMAX
+").unwrap();
+
+        let mut cap = d.create_attribute_by_name("WiMAX-Capability", vec![]).unwrap();
+        cap.add_child(d.create_attribute_by_name("WiMAX-Release", b"1.0".to_vec()).unwrap()).unwrap();
+        cap.add_child(d.create_attribute_by_name("WiMAX-Accounting-Capabilities", vec![1]).unwrap()).unwrap();
+        assert!(cap.add_child(d.create_attribute_by_name("User-Name", vec![]).unwrap()).is_err());
+        assert_eq!(cap.get_bytes().unwrap(), [
+            0x1a, 0x11, 0x00, 0x00, 0x60, 0xb5, 0x01, 0x0b,
+            0x00, 0x01, 0x05, 0x31, 0x2e, 0x30, 0x02, 0x03,
+            0x01
+        ]);
+
+        let mut data = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(vec![0; 16]), Some(vec![cap]));
+        let profile = d.create_attribute_by_name("WiMAX-Hotline-Profile-Id", vec![0, 0, 0, 7]).unwrap();
+        assert_eq!(profile.get_definition().get_oid(), "26.24757.1.3.1");
+        data.add_nested_attribute(profile).unwrap();
+        assert_eq!(data.get_attributes().len(), 1);
+
+        let data = d.load_bytes(&data.get_bytes().unwrap()).unwrap();
+        assert_eq!(data.get_attribute_by_path("WiMAX-Capability.WiMAX-Release").unwrap().get_value(), b"1.0");
+        assert_eq!(data.get_attribute_by_path("WiMAX-Capability.WiMAX-Hotlining-Capabilities.WiMAX-Hotline-Profile-Id").unwrap().get_value(), [0, 0, 0, 7]);
+        assert_eq!(data.get_attribute_by_oid("26.24757.1.2").unwrap().get_value(), [1]);
+        assert!(data.get_attribute_by_path("WiMAX-Capability.User-Name").is_none());
+    }
+
+    #[test]
+    fn includes() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("dictionary");
+
+        let d = Dictionary::from_dir(&root).unwrap();
+        assert!(d.create_attribute_by_name("User-Name", vec![]).is_ok());
+        assert!(d.create_attribute_by_name("3GPP-IMSI", vec![]).is_ok());
+        assert_eq!(d.get_value("Service-Type", "Framed-User"), Some(2));
+
+        let e = Dictionary::from_file(root.join("cycle").join("dictionary")).err().unwrap();
+        assert!(e.to_string().ends_with("include cycle detected"), "{}", e);
+
+        let e = Dictionary::from_dir(root.join("broken")).err().unwrap();
+        assert!(e.get_source().unwrap().ends_with("dictionary.broken"), "{}", e);
+        assert_eq!((e.get_line(), e.get_column()), (2, 29));
+        assert!(e.get_message().starts_with("Malformed attriute code"), "{}", e);
+        assert!(e.to_string().contains("dictionary.broken:2:29: Malformed attriute code"), "{}", e);
+    }
+
+    #[test]
+    fn duplicates() {
+        let base = "ATTRIBUTE User-Name 1 string\nATTRIBUTE Service-Type 6 integer\nVALUE Service-Type Login-User 1\n";
+        let d = Dictionary::from_str(&format!("{}{}", base, r"
+ATTRIBUTE   User-Name       1   string
+VALUE   Service-Type    Login-User  1
+VALUE   Service-Type    Login       1
+")).unwrap();
+        assert_eq!(d.get_attributes().iter().filter(|def| def.get_name() == "User-Name").count(), 1);
+        assert_eq!(d.get_value_name("Service-Type", 1), Some("Login-User"));
+        assert_eq!(d.get_value("Service-Type", "Login"), Some(1));
+
+        let e = Dictionary::from_str(&format!("{}{}", base, "ATTRIBUTE User-Name 1 octets")).err().unwrap();
+        assert_eq!(e.get_message(), "Conflicting definition of attribute User-Name", "{}", e);
+        let e = Dictionary::from_str(&format!("{}{}", base, "ATTRIBUTE Login-Name 1 string")).err().unwrap();
+        assert_eq!(e.get_message(), "Attribute Login-Name conflicts with User-Name (1)", "{}", e);
+        let e = Dictionary::from_str(&format!("{}{}", base, "VALUE Service-Type Login-User 2")).err().unwrap();
+        assert_eq!(e.get_message(), "Conflicting value Login-User of attribute Service-Type: 1 and 2", "{}", e);
+        assert!(Dictionary::from_str("VENDOR Test 1\nVENDOR Other 1").is_err());
+    }
+
+    #[test]
+    fn layering() {
+        let mut d = Dictionary::default();
+        d.extend_from_str("VENDOR Site 99999\nATTRIBUTE Site-Role 26.99999.1 string").unwrap();
+        assert_eq!(d.create_attribute_by_name("Site-Role", b"x".to_vec()).unwrap().get_bytes().unwrap(), [26, 9, 0, 1, 0x86, 0x9f, 1, 3, b'x']);
+
+        let def = d.parse_attribute_row("ATTRIBUTE Site-Level 26.99999.2 integer").unwrap();
+        d.register_attribute(def).unwrap();
+        let def = d.parse_attribute_row("ATTRIBUTE Site-Group 26.99999.2 string").unwrap();
+        assert!(d.register_attribute(def.clone()).is_err());
+        let replaced = d.override_attribute(def);
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(replaced[0].get_name(), "Site-Level");
+        assert!(d.create_attribute_by_name("Site-Level", vec![]).is_err());
+        assert_eq!(d.create_vendor_attribute_by_code(99999, 2, vec![]).unwrap().get_definition().get_name(), "Site-Group");
+
+        assert!(d.remove_attribute("Service-Type").is_some());
+        assert!(d.create_attribute_by_code(6, vec![]).is_err());
+        assert!(d.get_values("Service-Type").is_empty());
+        assert!(d.create_attribute_by_name("Framed-Protocol", vec![]).is_ok());
+
+        let other = Dictionary::from_str("ATTRIBUTE User-Name 1 octets\nATTRIBUTE Service-Type 6 integer\nVALUE Framed-Protocol PPP 42").unwrap();
+        let mut existing = Dictionary::default();
+        assert!(existing.merge(Dictionary::from_str("ATTRIBUTE User-Name 1 octets").unwrap(), Precedence::Strict).is_err());
+        existing.merge(Dictionary::from_str("ATTRIBUTE User-Name 1 octets\nVALUE Framed-Protocol PPP 42").unwrap(), Precedence::Existing).unwrap();
+        assert_eq!(*existing.create_attribute_by_name("User-Name", vec![]).unwrap().get_definition().get_type(), RadiusAttributeType::String);
+        assert_eq!(existing.get_value("Framed-Protocol", "PPP"), Some(1));
+
+        d.merge(other, Precedence::Incoming).unwrap();
+        assert_eq!(*d.create_attribute_by_name("User-Name", vec![]).unwrap().get_definition().get_type(), RadiusAttributeType::Octets);
+        assert!(d.create_attribute_by_code(6, vec![]).is_ok());
+        assert_eq!(d.get_value("Framed-Protocol", "PPP"), Some(42));
+
+        // a failed merge leaves the dictionary untouched
+        let mut existing = Dictionary::default();
+        assert!(existing.merge(Dictionary::from_str("ATTRIBUTE Site-Level 240 integer\nATTRIBUTE User-Name 1 octets").unwrap(), Precedence::Strict).is_err());
+        assert!(existing.create_attribute_by_name("Site-Level", vec![]).is_err());
+
+        // replaced vendors are seen by their attributes
+        d.merge(Dictionary::from_str("VENDOR Site 99999 format=2,1").unwrap(), Precedence::Incoming).unwrap();
+        assert_eq!(d.create_attribute_by_name("Site-Role", b"x".to_vec()).unwrap().get_bytes().unwrap(), [26, 10, 0, 1, 0x86, 0x9f, 0, 1, 4, b'x']);
+
+        // only the conflicting vendor is replaced, attributes of a vendor replaced under another id go with it
+        let vendors = "VENDOR Site 99999\nATTRIBUTE Site-Role 26.99999.1 string\nVENDOR Other 88888\nATTRIBUTE Other-Role 26.88888.1 string";
+        let mut d = Dictionary::from_str(vendors).unwrap();
+        assert_eq!(d.merge(Dictionary::from_str("VENDOR Site 88888").unwrap(), Precedence::Incoming).err().unwrap(), "Vendor Site (88888) conflicts with Site (99999) and Other (88888)");
+        d.merge(Dictionary::from_str("VENDOR Other 88888 format=2,1").unwrap(), Precedence::Incoming).unwrap();
+        assert_eq!(d.create_attribute_by_name("Site-Role", b"x".to_vec()).unwrap().get_bytes().unwrap(), [26, 9, 0, 1, 0x86, 0x9f, 1, 3, b'x']);
+        assert_eq!(d.create_attribute_by_name("Other-Role", b"x".to_vec()).unwrap().get_bytes().unwrap(), [26, 10, 0, 1, 0x5b, 0x38, 0, 1, 4, b'x']);
+        d.merge(Dictionary::from_str("VENDOR Site 77777").unwrap(), Precedence::Incoming).unwrap();
+        assert_eq!(d.get_vendor_by_name("Site").unwrap().get_id(), 77777);
+        assert!(d.get_vendor_by_id(99999).is_none());
+        assert!(d.create_attribute_by_name("Site-Role", vec![]).is_err());
+        assert!(d.create_attribute_by_name("Other-Role", vec![]).is_ok());
+
+        // values conflicting on name or number are replaced, in the order they come in
+        let mut d = Dictionary::default();
+        d.merge(Dictionary::from_str("ATTRIBUTE Service-Type 6 integer\nVALUE Service-Type Framed-Service 2\nVALUE Service-Type Login-User 100\nVALUE Service-Type Framed 2").unwrap(), Precedence::Incoming).unwrap();
+        assert_eq!(d.get_value_name("Service-Type", 2), Some("Framed-Service"));
+        assert_eq!(d.get_value("Service-Type", "Framed-User"), None);
+        assert_eq!(d.get_value("Service-Type", "Framed"), Some(2));
+        assert_eq!(d.get_value("Service-Type", "Login-User"), Some(100));
+        assert_eq!(d.get_value_name("Service-Type", 1), None);
+        let names: Vec<&str> = d.get_values("Service-Type").iter().map(|v| v.get_name()).rev().take(3).collect();
+        assert_eq!(names, ["Framed", "Login-User", "Framed-Service"]);
+
+        // nested definitions follow their parent, or go away with it
+        let mut d = Dictionary::from_str("ATTRIBUTE Test-Tlv 240 tlv\nATTRIBUTE Test-Sub 240.1 string\nATTRIBUTE Test-Other 241 tlv\nATTRIBUTE Test-Other-Sub 241.1 string").unwrap();
+        let parent = |d: &Dictionary| d.create_attribute_by_name("Test-Sub", vec![]).map(|a| a.get_definition().get_parent().unwrap().get_name().to_owned());
+        d.override_attribute(d.parse_attribute_row("ATTRIBUTE Test-Container 240 tlv").unwrap());
+        assert_eq!(parent(&d).unwrap(), "Test-Container");
+        let replaced: Vec<String> = d.override_attribute(d.parse_attribute_row("ATTRIBUTE Test-Flat 240 string").unwrap()).iter().map(|def| def.get_name().to_owned()).collect();
+        assert_eq!(replaced, ["Test-Container", "Test-Sub"]);
+        assert!(parent(&d).is_err());
+        assert!(d.remove_attribute("Test-Other").is_some());
+        assert!(d.create_attribute_by_name("Test-Other-Sub", vec![]).is_err());
+    }
+
+    #[test]
+    fn export() {
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   User-Password   2   string  encrypt=1
+ATTRIBUTE   Service-Type    6   integer
+ATTRIBUTE   Extended-Attribute-1    241 extended
+ATTRIBUTE   Extended-Vendor-Specific-1  241.26  evs
+VALUE   Service-Type    Login-User  1
+VENDOR  WiMAX   24757   format=1,1,c
+VENDOR  Test    99
+BEGIN-VENDOR    WiMAX
+ATTRIBUTE   WiMAX-Capability    1   tlv
+ATTRIBUTE   WiMAX-Release       1.1 string
+END-VENDOR      WiMAX
+BEGIN-VENDOR    Test    format=Extended-Vendor-Specific-1
+ATTRIBUTE   Test-Evs            1   integer has_tag
+END-VENDOR      Test
+VALUE   Test-Evs        One         1
+VALUE   Unknown-Attr    Two         2
+").unwrap();
+
+        let text = d.to_string();
+        assert!(text.contains("VENDOR\tWiMAX\t24757\tformat=1,1,c\nVENDOR\tTest\t99\n"), "{}", text);
+        assert!(text.contains("BEGIN-VENDOR\tWiMAX\nATTRIBUTE\tWiMAX-Capability\t1\ttlv\nATTRIBUTE\tWiMAX-Release\t1.1\tstring\nEND-VENDOR\tWiMAX\n"), "{}", text);
+        assert!(text.contains("BEGIN-VENDOR\tTest\tformat=Extended-Vendor-Specific-1\nATTRIBUTE\tTest-Evs\t1\tinteger\thas_tag\nEND-VENDOR\tTest\n"), "{}", text);
+        assert!(text.contains("ATTRIBUTE\tUser-Password\t2\tstring\tencrypt=1\n"), "{}", text);
+        assert!(text.ends_with("VALUE\tTest-Evs\tOne\t1\nVALUE\tUnknown-Attr\tTwo\t2\n"), "{}", text);
+
+        let e = Dictionary::from_str(&text).unwrap();
+        assert!(e.get_attributes() == d.get_attributes());
+        assert!(e.get_vendors() == d.get_vendors());
+        assert!(e.get_values("Service-Type") == d.get_values("Service-Type"));
+        assert_eq!(e.to_string(), text);
+
+        let json = d.to_json();
+        assert!(json.starts_with("{\n  \"vendors\": [\n    {\"name\": \"WiMAX\", \"id\": 24757, \"type_size\": 1, \"length_size\": 1, \"continuation\": true},\n"), "{}", json);
+        assert!(json.contains(r#"{"name": "Test-Evs", "oid": "241.26.99.1", "code": 1, "type": "integer", "vendor": 99, "parent": "241.26", "flags": {"has_tag": true, "encrypt": null, "concat": false, "array": false}}"#), "{}", json);
+        assert!(json.contains(r#"{"name": "User-Password", "oid": "2", "code": 2, "type": "string", "vendor": null, "parent": null, "flags": {"has_tag": false, "encrypt": 1, "concat": false, "array": false}}"#), "{}", json);
+        assert!(json.ends_with("{\"attribute\": \"Unknown-Attr\", \"name\": \"Two\", \"value\": 2}\n  ]\n}\n"), "{}", json);
+    }
+
+    #[test]
+    fn iana() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("dictionary");
+        let d = Dictionary::from_iana_csv(&std::fs::read_to_string(root.join("radius-types-2.csv")).unwrap()).unwrap();
+
+        let atype = |name: &str| d.create_attribute_by_name(name, vec![]).unwrap().get_definition().get_type().clone();
+        assert_eq!(atype("User-Name"), RadiusAttributeType::String);
+        assert_eq!(atype("User-Password"), RadiusAttributeType::Octets);
+        assert_eq!(atype("NAS-IP-Address"), RadiusAttributeType::IpAddr);
+        assert_eq!(atype("Service-Type"), RadiusAttributeType::Integer);
+        assert_eq!(atype("Event-Timestamp"), RadiusAttributeType::Date);
+        assert_eq!(atype("PMIP6-Home-IPv4-HoA"), RadiusAttributeType::Ipv4Prefix);
+        assert!(d.create_attribute_by_name("EAP-Message", vec![]).unwrap().get_definition().get_flags().is_concat());
+        assert_eq!(d.create_attribute_by_oid("241.1", vec![]).unwrap().get_definition().get_name(), "Frag-Status");
+        assert_eq!(d.create_attribute_by_oid("245.26", vec![]).unwrap().get_definition().get_name(), "Extended-Vendor-Specific-5");
+        assert!(d.create_attribute_by_oid("241.12", vec![]).is_err());
+
+        let e = Dictionary::from_iana_csv("Value,Description,Data Type,Reference\n1,User-Name,text,[RFC2865]\n2,User-Password,foo,\"[RFC2865]\"\n").err().unwrap();
+        assert_eq!((e.get_line(), e.get_column(), e.get_message()), (3, 17, "Unrecognized Attribute type: foo"));
+    }
+
+    #[test]
+    fn default_dictionary() {
+        // DEFAULT_DICTIONARY follows the registry, keeping FreeRADIUS spelling and flags
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("dictionary");
+        let registry = Dictionary::from_iana_csv(&std::fs::read_to_string(root.join("radius-types-2.csv")).unwrap()).unwrap();
+        let default = Dictionary::default();
+
+        let row = |def: &RadiusAttributeDef| format!("{} {} {}", def.get_oid(), def.get_name(), def.get_type().get_name());
+        let mut diff = Vec::new();
+        for def in registry.get_attributes() {
+            match default.get_attributes().iter().find(|d| d.get_oid() == def.get_oid()) {
+                Some(d) if d.get_name().eq_ignore_ascii_case(def.get_name()) && d.get_type() == def.get_type() => {},
+                Some(d) => diff.push(format!("-{}\n+{}", row(def), row(d))),
+                None => diff.push(format!("-{}", row(def))),
+            }
+        }
+        for def in default.get_attributes().iter().filter(|d| d.get_vendor().is_none()) {
+            if registry.get_attributes().iter().all(|r| r.get_oid() != def.get_oid()) {
+                diff.push(format!("+{}", row(def)));
+            }
+        }
+
+        assert_eq!(diff, [
+            // clashes with the draft Digest-Response (206)
+            "-103 Digest-Response string",
+            // no FreeRADIUS data type for 2 byte values
+            "-141 PKM-SAID octets\n+141 PKM-SAID short",
+            // FreeRADIUS names of the extended spaces
+            "-241 Extended-Type-1 extended\n+241 Extended-Attribute-1 extended",
+            "-242 Extended-Type-2 extended\n+242 Extended-Attribute-2 extended",
+            "-243 Extended-Type-3 extended\n+243 Extended-Attribute-3 extended",
+            "-244 Extended-Type-4 extended\n+244 Extended-Attribute-4 extended",
+            "-245 Extended-Type-5 long-extended\n+245 Extended-Attribute-5 long-extended",
+            "-246 Extended-Type-6 long-extended\n+246 Extended-Attribute-6 long-extended",
+            // RFC 6930 sub-options and draft attributes, not in the registry
+            "+173.1 IPv6-6rd-IPv4MaskLen integer",
+            "+173.2 IPv6-6rd-Prefix ipv6prefix",
+            "+173.3 IPv6-6rd-BR-IPv4-Address ipaddr",
+            "+206 Digest-Response string",
+            "+207 Digest-Attributes octets",
+        ], "{}", diff.join("\n"));
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(Dictionary::validate(DEFAULT_DICTIONARY), []);
+
+        let errors = Dictionary::validate(r"
+ATTRIBUTE   User-Name       1   string
+ATTRIBUTE   User-Name       1   string
+FLAGS   internal
+VALUE   Service-Type    Login-User  1
+ATTRIBUTE   Port-Limit      62  byte
+VALUE   Port-Limit      Many        300
+VALUE   User-Name       Admin       1
+ATTRIBUTE   NAS-Port        5   foo
+ATTRIBUTE   Class           25  octets
+");
+        let found: Vec<(usize, usize, &str)> = errors.iter().map(|e| (e.get_line(), e.get_column(), e.get_message())).collect();
+        assert_eq!(found, [
+            (3, 13, "Duplicate definition of attribute User-Name"),
+            (4, 1, "Unrecognized keyword: FLAGS"),
+            (5, 9, "Value Login-User of undeclared attribute Service-Type"),
+            (7, 37, "Value 300 out of byte attribute Port-Limit range"),
+            (8, 9, "Value Admin of string attribute User-Name"),
+            (9, 33, "Unrecognized Attribute type: foo"),
+        ]);
+        assert_eq!(errors[0].to_string(), "line 3 column 13: Duplicate definition of attribute User-Name");
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("dictionary");
+        assert_eq!(Dictionary::validate_dir(root.join("broken")).len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let d = Dictionary::default();
+        let mut data = RadiusData::new(RadiusCode::AccessAccept, Some(7), Some(vec![0; 16]), None);
+        for a in d.parse_attributes("User-Name = \"testing\", Service-Type = Framed-User, Framed-IP-Address = 10.0.0.1, Tunnel-Type:1 = VLAN, Class = 0x0102").unwrap() {
+            data.add_attribute(a);
+        }
+        data.add_attribute(d.create_attribute_by_name("Session-Timeout", vec![1]).unwrap());
+
+        let json = serde_json::to_string(&data).unwrap();
+        assert_eq!(json, concat!(
+            r#"{"code":2,"identifier":7,"authenticator":"00000000000000000000000000000000","attributes":["#,
+            r#"{"name":"User-Name","value":"testing"},{"name":"Service-Type","value":2},{"name":"Framed-IP-Address","value":"10.0.0.1"},"#,
+            r#"{"name":"Tunnel-Type","tag":1,"value":13},{"name":"Class","hex":"0102"},{"name":"Session-Timeout","hex":"01"}]}"#,
+        ));
+
+        let back = d.deserialize_data(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        assert_eq!(back.get_bytes().unwrap(), data.get_bytes().unwrap());
+
+        let named = d.deserialize_data(&mut serde_json::Deserializer::from_str(r#"{"code":1,"identifier":1,"authenticator":"00000000000000000000000000000000","attributes":[{"name":"Service-Type","value":"Login-User"}]}"#)).unwrap();
+        assert_eq!(named.get_u32("Service-Type").unwrap(), 1);
+        assert!(d.deserialize_data(&mut serde_json::Deserializer::from_str(r#"{"code":1,"identifier":1,"authenticator":"00","attributes":[{"name":"NAS-Port","value":"x"}]}"#)).is_err());
+    }
+
+    #[test]
+    fn vendors() {
+        let d = Dictionary::default();
+        assert!(d.create_attribute_by_name("User-Name", vec![]).is_ok());
+
+        #[cfg(feature = "vendor-3gpp")]
+        {
+            assert_eq!(Dictionary::validate(&format!("{}{}", DEFAULT_DICTIONARY, super::Vendor::ThreeGpp.get_dictionary())), []);
+            let d = Dictionary::default().with_vendor(super::Vendor::ThreeGpp).unwrap();
+            let attr = d.create_attribute_by_value("3GPP-RAT-Type", "EUTRAN").unwrap();
+            assert_eq!(attr.get_bytes().unwrap(), [26, 9, 0, 0, 0x28, 0xaf, 21, 3, 6]);
+        }
+        #[cfg(feature = "vendor-wimax")]
+        {
+            assert_eq!(Dictionary::validate(&format!("{}{}", DEFAULT_DICTIONARY, super::Vendor::WiMax.get_dictionary())), []);
+            let d = Dictionary::default().with_vendor(super::Vendor::WiMax).unwrap();
+            let attr = d.create_vendor_attribute_by_code(24757, 2, vec![1]).unwrap();
+            assert_eq!(attr.get_bytes().unwrap(), [26, 10, 0, 0, 0x60, 0xb5, 2, 4, 0, 1]);
+        }
+        #[cfg(all(feature = "vendor-3gpp2", feature = "vendor-microsoft", feature = "vendor-cisco", feature = "vendor-juniper", feature = "vendor-dsl-forum"))]
+        {
+            use super::Vendor;
+            let d = [Vendor::ThreeGpp2, Vendor::Microsoft, Vendor::Cisco, Vendor::Juniper, Vendor::DslForum].iter()
+                .try_fold(Dictionary::default(), |d, vendor| d.with_vendor(*vendor)).unwrap();
+            for name in ["3GPP2-BSID", "MS-CHAP2-Response", "Cisco-AVPair", "Juniper-Local-User-Name", "ADSL-Agent-Circuit-Id"] {
+                assert!(d.create_attribute_by_name(name, vec![]).is_ok(), "{}", name);
+            }
+            for vendor in [Vendor::ThreeGpp2, Vendor::Microsoft, Vendor::Cisco, Vendor::Juniper, Vendor::DslForum] {
+                assert_eq!(Dictionary::validate(&format!("{}{}", DEFAULT_DICTIONARY, vendor.get_dictionary())), [], "{:?}", vendor);
+            }
+
+            // conflicting definitions are reported instead of panicking
+            let e = Dictionary::from_str("ATTRIBUTE MS-CHAP-Response 1 string").unwrap().with_vendor(Vendor::Microsoft).err().unwrap();
+            assert!(e.get_message().contains("MS-CHAP-Response"), "{}", e);
+        }
     }
 }
@@ -1,17 +1,21 @@
-use super::{Dictionary, RadiusCode, RadiusAttribute, RadiusData};
+use super::dictionary::{PacketError, Precedence};
+use super::{Dictionary, RadiusCode, RadiusAttribute, RadiusData, RadiusEncryption, IntoRadiusValue, MAX_VALUE_LENGTH};
+
+use std::convert::TryFrom;
 
 use crypto::digest::Digest;
 use crypto::md5::Md5;
 use crypto::mac::Mac;
 use crypto::hmac::Hmac;
 
+use rand::{thread_rng, Rng};
+
 /// Host management
 pub struct Host {
     dict: Dictionary,
     authport: usize,
     accport: usize,
-    // TODO: find out which operations goes on coaport
-    _coaport: usize,
+    coaport: usize,
 }
 
 impl Host {
@@ -20,18 +24,23 @@ impl Host {
             dict,
             authport,
             accport,
-            _coaport: coaport,
+            coaport,
         }
     }
 
     pub fn get_port(&self, code: &RadiusCode) -> usize {
         match code {
             RadiusCode::AccessRequest => self.authport,
-            //RadiusCode::Co => self.coaport,
+            // dynamic authorization (RFC 5176)
+            RadiusCode::CoaRequest | RadiusCode::DisconnectRequest => self.coaport,
             _ => self.accport,
         }
     }
 
+    pub fn merge_dictionary(&mut self, dict: Dictionary, precedence: Precedence) -> Result<(), String> {
+        self.dict.merge(dict, precedence)
+    }
+
     pub fn create_attribute_by_name(&self, name: &str, value: Vec<u8>) -> Result<RadiusAttribute, String> {
         self.dict.create_attribute_by_name(name, value)
     }
@@ -40,32 +49,159 @@ impl Host {
         self.dict.create_attribute_by_code(code, value)
     }
 
+    pub fn create_attribute_by_value(&self, name: &str, value: &str) -> Result<RadiusAttribute, String> {
+        self.dict.create_attribute_by_value(name, value)
+    }
+
+    /// Creates a RadiusAttribute element from a native value, encoded after the attribute data type
+    pub fn create_attribute<T: IntoRadiusValue>(&self, name: &str, value: T) -> Result<RadiusAttribute, String> {
+        self.dict.create_attribute(name, value)
+    }
+
+    /// Creates a tagged RadiusAttribute element from a native value, e.g. for Tunnel-* attributes
+    pub fn create_tagged_attribute<T: IntoRadiusValue>(&self, name: &str, tag: u8, value: T) -> Result<RadiusAttribute, String> {
+        self.dict.create_tagged_attribute(name, tag, value)
+    }
+
     /// generates an authentication request packet
+    /// User-Password is hidden and Message-Authenticator signed, the packet being ready to go on the wire
     pub fn get_auth_packet(&self, username: &str, password: &str, secret: &[u8], identifier: Option<u8>, authenticator: Option<Vec<u8>>, attributes: Option<Vec<RadiusAttribute>>) -> Result<RadiusData, String> {
         let mut data = RadiusData::new(RadiusCode::AccessRequest, identifier, authenticator, attributes);
 
         data.add_attribute(self.dict.create_attribute_by_name("User-Name", username.as_bytes().to_vec())?);
+        data.add_attribute(self.dict.create_attribute_by_name("User-Password", password.as_bytes().to_vec())?);
+        if data.get_attribute_by_name("Message-Authenticator").is_none() {
+            data.add_attribute(self.dict.create_attribute_by_name("Message-Authenticator", vec![0; 16])?);
+        }
+        self.seal(&mut data, secret)?;
 
-        let pwd = self.crypt_password(password, data.get_authenticator(), secret);
-        data.add_attribute(self.dict.create_attribute_by_name("User-Password", pwd)?);
+        Ok(data)
+    }
 
-        let temp = self.generate_hash(&data, secret)?;
-        let add = match data.get_attribute_by_name_mut("Message-Authenticator") {
-            Some(v) => {
-                v.set_value(temp);
-                None
-            },
-            None => Some(temp),
-        };
-        if add.is_some() {
-            data.add_attribute(self.dict.create_attribute_by_name("Message-Authenticator", add.unwrap())?);
+    /// packet bytes as sent, attributes flagged with encrypt=1 or encrypt=2 not yet hidden being hidden
+    /// with the packet authenticator and Message-Authenticator, when present, signed
+    pub fn encode_packet(&self, data: &RadiusData, secret: &[u8]) -> Result<Vec<u8>, String> {
+        let mut data = data.clone();
+        self.seal(&mut data, secret)?;
+        data.get_bytes()
+    }
+
+    // hides the attributes to be hidden and signs Message-Authenticator, when present
+    fn seal(&self, data: &mut RadiusData, secret: &[u8]) -> Result<(), String> {
+        self.encrypt_attributes(data, secret)?;
+
+        if let Some(i) = data.get_attributes().iter().position(|a| a.get_definition().get_name() == "Message-Authenticator") {
+            let hash = self.generate_hash(data, secret)?;
+            data.get_attributes_mut()[i].set_value(hash);
         }
+        Ok(())
+    }
 
-        Ok(data)
+    /// hides the values of attributes flagged with encrypt=1 (RFC 2865 User-Password scheme)
+    /// or encrypt=2 (RFC 2868 salted scheme, with a random salt per attribute), nested ones included
+    /// values already hidden are left alone
+    fn encrypt_attributes(&self, data: &mut RadiusData, secret: &[u8]) -> Result<(), String> {
+        let authenticator = data.get_authenticator().to_vec();
+        self.encrypt_all(data.get_attributes_mut(), &authenticator, secret)
     }
 
-    fn crypt_password(&self, password: &str, authenticator: &[u8], secret: &[u8]) -> Vec<u8> {
-        let mut buf = password.as_bytes().to_vec();
+    fn encrypt_all(&self, attributes: &mut [RadiusAttribute], authenticator: &[u8], secret: &[u8]) -> Result<(), String> {
+        let mut rng = thread_rng();
+        for attr in attributes.iter_mut() {
+            self.encrypt_all(attr.get_children_mut(), authenticator, secret)?;
+            if attr.is_hidden() {
+                continue;
+            }
+            let value = match attr.get_definition().get_flags().get_encrypt() {
+                Some(RadiusEncryption::UserPassword) => self.crypt_password(attr.get_value(), authenticator, secret),
+                Some(RadiusEncryption::TunnelPassword) => self.salt_encrypt(attr.get_value(), authenticator, secret, [0x80 | rng.gen::<u8>(), rng.gen::<u8>()])
+                    .map_err(|e| format!("{}: {}", attr.get_definition().get_name(), e))?,
+                _ => continue,
+            };
+            attr.set_value(value);
+            attr.set_hidden(true);
+        }
+        Ok(())
+    }
+
+    /// reveals the values of reply attributes flagged with encrypt=2 (RFC 2868 salted scheme), nested ones included,
+    /// keyed on the authenticator of the originating request
+    /// values that can't be revealed are left hidden, their errors being returned
+    pub fn decrypt_attributes(&self, data: &mut RadiusData, authenticator: &[u8], secret: &[u8]) -> Vec<String> {
+        let mut errors = Vec::new();
+        self.decrypt_all(data.get_attributes_mut(), authenticator, secret, &mut errors);
+        errors
+    }
+
+    fn decrypt_all(&self, attributes: &mut [RadiusAttribute], authenticator: &[u8], secret: &[u8], errors: &mut Vec<String>) {
+        for attr in attributes.iter_mut() {
+            self.decrypt_all(attr.get_children_mut(), authenticator, secret, errors);
+            if attr.is_hidden() && attr.get_definition().get_flags().get_encrypt() == Some(RadiusEncryption::TunnelPassword) {
+                match self.salt_decrypt(attr.get_value(), authenticator, secret) {
+                    Ok(value) => attr.set_value(value),
+                    Err(e) => errors.push(format!("{}: {}", attr.get_definition().get_name(), e)),
+                }
+            }
+        }
+    }
+
+    // RFC 2868 salted encryption: salt followed by the hidden length prefixed value
+    // fails when the length doesn't fit its byte or the padded result doesn't fit an attribute
+    fn salt_encrypt(&self, value: &[u8], authenticator: &[u8], secret: &[u8], salt: [u8; 2]) -> Result<Vec<u8>, String> {
+        let mut buf = vec![u8::try_from(value.len()).map_err(|_| format!("Value too long to encrypt: {} bytes, maximum {}", value.len(), u8::MAX))?];
+        buf.extend_from_slice(value);
+        let l = buf.len() % 16;
+        if l != 0 {
+            buf.append(&mut vec![0u8; 16 - l]);
+        }
+        if 2 + buf.len() > MAX_VALUE_LENGTH {
+            return Err(format!("Encrypted value too long: {} bytes, maximum {}", 2 + buf.len(), MAX_VALUE_LENGTH));
+        }
+
+        let mut result = salt.to_vec();
+        let mut last = authenticator.to_vec();
+        last.extend_from_slice(&salt);
+        for chunk in buf.chunks(16) {
+            let hash = Self::md5(secret, &last);
+            last = chunk.iter().zip(hash.iter()).map(|(b, h)| b ^ h).collect();
+            result.extend_from_slice(&last);
+        }
+
+        Ok(result)
+    }
+
+    fn salt_decrypt(&self, value: &[u8], authenticator: &[u8], secret: &[u8]) -> Result<Vec<u8>, String> {
+        if value.len() < 18 || (value.len() - 2) % 16 != 0 {
+            return Err(format!("Malformed salt encrypted value: {} bytes", value.len()));
+        }
+
+        let mut plain = Vec::new();
+        let mut last = authenticator.to_vec();
+        last.extend_from_slice(&value[..2]);
+        for chunk in value[2..].chunks(16) {
+            let hash = Self::md5(secret, &last);
+            plain.extend(chunk.iter().zip(hash.iter()).map(|(b, h)| b ^ h));
+            last = chunk.to_vec();
+        }
+
+        let len = plain[0] as usize;
+        if len >= plain.len() {
+            return Err(format!("Invalid salt encrypted value length: {}", len));
+        }
+        Ok(plain[1..=len].to_vec())
+    }
+
+    fn md5(secret: &[u8], data: &[u8]) -> [u8; 16] {
+        let mut md5 = Md5::new();
+        md5.input(secret);
+        md5.input(data);
+        let mut hash = [0; 16];
+        md5.result(&mut hash);
+        hash
+    }
+
+    fn crypt_password(&self, password: &[u8], authenticator: &[u8], secret: &[u8]) -> Vec<u8> {
+        let mut buf = password.to_vec();
         let l = buf.len() % 16;
         if l != 0 {
             buf.append(&mut vec![0u8; 16 - l]);
@@ -109,11 +245,11 @@ impl Host {
         }
 
         let mut hmac = Hmac::new(Md5::new(), secret);
-        hmac.input(&temp.get_bytes());
+        hmac.input(&temp.get_bytes()?);
         Ok(hmac.result().code().to_vec())
     }
 
-    pub fn load_bytes(&self, data: &[u8]) -> Result<RadiusData, String> {
+    pub fn load_bytes(&self, data: &[u8]) -> Result<RadiusData, PacketError> {
         self.dict.load_bytes(data)
     }
 }
@@ -122,6 +258,7 @@ impl Host {
 mod tests {
     use super::{Host, Dictionary};
     use crate::client::dictionary::DEFAULT_DICTIONARY;
+    use crate::radius::{RadiusCode, RadiusData};
     use std::str::FromStr;
 
     #[test]
@@ -142,11 +279,142 @@ mod tests {
         let h = Host::new(1812, 1813, 3799, Dictionary::from_str(DEFAULT_DICTIONARY).unwrap());
 
         let data = h.load_bytes(&example).unwrap();
-        assert_eq!(example.to_vec(), data.get_bytes());
+        assert_eq!(example.to_vec(), data.get_bytes().unwrap());
 
         match data.get_attribute_by_name("Message-Authenticator") {
             Some(v) => assert_eq!(v.get_value().to_vec(), h.generate_hash(&data, "SECRET".as_bytes()).unwrap()),
             None => panic!("Message-Authenticator not found in message"),
         }
     }
+
+    #[test]
+    fn user_password() {
+        let authenticator = vec![
+            0x8a, 0xa8, 0x1f, 0xc1, 0x74, 0xc8, 0x63, 0x10,
+            0x21, 0x13, 0xf2, 0xe7, 0x5b, 0xf4, 0x69, 0x61
+        ];
+
+        let h = Host::new(1812, 1813, 3799, Dictionary::from_str(DEFAULT_DICTIONARY).unwrap());
+
+        let data = h.get_auth_packet("testing", "password", "SECRET".as_bytes(), Some(0x68), Some(authenticator), None).unwrap();
+        assert_eq!(data.get_attribute_by_name("User-Password").unwrap().get_value(), [
+            0xdb, 0x45, 0x98, 0x63, 0x4f, 0x11, 0x03, 0x24,
+            0x4a, 0x4f, 0x9d, 0x8f, 0xa6, 0x02, 0x16, 0x46
+        ]);
+        assert_eq!(data.get_attribute_by_name("Message-Authenticator").unwrap().get_value(), h.generate_hash(&data, "SECRET".as_bytes()).unwrap().as_slice());
+
+        // ready to go on the wire, and not hidden twice on the way out
+        let bytes = data.get_bytes().unwrap();
+        assert!(!bytes.windows(8).any(|w| w == b"password"));
+        assert_eq!(h.encode_packet(&data, "SECRET".as_bytes()).unwrap(), bytes);
+
+        // packets built otherwise are hidden on the way out, never encoded in clear
+        let mut data = RadiusData::new(RadiusCode::AccessRequest, Some(0x68), Some(data.get_authenticator().to_vec()), None);
+        data.add_attribute(h.create_attribute("User-Password", "password").unwrap());
+        assert_eq!(data.get_bytes().err().unwrap(), "Value of User-Password not hidden, the packet must be encoded with encode_packet");
+        let bytes = h.encode_packet(&data, "SECRET".as_bytes()).unwrap();
+        assert!(!bytes.windows(8).any(|w| w == b"password"));
+        let sent = h.load_bytes(&bytes).unwrap();
+        assert!(sent.get_attribute_by_name("User-Password").unwrap().is_hidden());
+        assert_eq!(sent.get_attribute_by_name("User-Password").unwrap().get_value()[..2], [0xdb, 0x45]);
+        assert_eq!(sent.get_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn salt_encryption() {
+        let authenticator = vec![
+            0x8a, 0xa8, 0x1f, 0xc1, 0x74, 0xc8, 0x63, 0x10,
+            0x21, 0x13, 0xf2, 0xe7, 0x5b, 0xf4, 0x69, 0x61
+        ];
+
+        let h = Host::new(1812, 1813, 3799, Dictionary::from_str(DEFAULT_DICTIONARY).unwrap());
+        let secret = "SECRET".as_bytes();
+
+        let hidden = h.salt_encrypt(b"password", &authenticator, secret, [0x80, 0x01]).unwrap();
+        assert_eq!(hidden.len(), 18);
+        assert_eq!(hidden[..2], [0x80, 0x01]);
+        assert_eq!(h.salt_decrypt(&hidden, &authenticator, secret).unwrap(), b"password");
+        assert!(h.salt_decrypt(&hidden[..17], &authenticator, secret).is_err());
+        assert_eq!(h.salt_encrypt(&[0; 256], &authenticator, secret, [0x80, 0x01]).err().unwrap(), "Value too long to encrypt: 256 bytes, maximum 255");
+        assert_eq!(h.salt_encrypt(&[0; 240], &authenticator, secret, [0x80, 0x01]).err().unwrap(), "Encrypted value too long: 258 bytes, maximum 253");
+        assert_eq!(h.salt_encrypt(&[0; 239], &authenticator, secret, [0x80, 0x01]).unwrap().len(), 242);
+
+        // reply hidden with the request authenticator, decoded from the wire
+        let mut reply = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(authenticator.clone()), None);
+        let tunnel = h.create_tagged_attribute("Tunnel-Password", 1, b"tunnel secret".to_vec()).unwrap();
+        reply.add_attribute(tunnel);
+        let bytes = h.encode_packet(&reply, secret).unwrap();
+        assert_eq!(bytes[20..23], [69, 21, 1]);
+        assert!(bytes[23] & 0x80 != 0);
+
+        let mut decoded = h.load_bytes(&bytes).unwrap();
+        assert!(h.decrypt_attributes(&mut decoded, &authenticator, secret).is_empty());
+        let tunnel = decoded.get_attribute_by_name("Tunnel-Password").unwrap();
+        assert_eq!((tunnel.get_tag(), tunnel.get_value()), (Some(1), &b"tunnel secret"[..]));
+
+        reply.get_attribute_by_name_mut("Tunnel-Password").unwrap().set_value(vec![0; 240]);
+        assert_eq!(h.encode_packet(&reply, secret).err().unwrap(), "Tunnel-Password: Encrypted value too long: 258 bytes, maximum 253");
+    }
+
+    #[test]
+    fn salt_decryption() {
+        let authenticator = vec![0x11; 16];
+        let secret = "SECRET".as_bytes();
+        let h = Host::new(1812, 1813, 3799, Dictionary::from_str(&format!("{}
+ATTRIBUTE   Test-Tlv        240     tlv
+ATTRIBUTE   Test-Secret     240.1   octets  encrypt=2
+", DEFAULT_DICTIONARY)).unwrap());
+
+        let mut reply = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(authenticator.clone()), None);
+        reply.add_attribute(h.create_tagged_attribute("Tunnel-Password", 1, b"tunnel secret".to_vec()).unwrap());
+        let mut tlv = h.create_attribute_by_name("Test-Tlv", Vec::new()).unwrap();
+        tlv.add_child(h.create_attribute_by_name("Test-Secret", b"nested secret".to_vec()).unwrap()).unwrap();
+        reply.add_attribute(tlv);
+        let mut bytes = h.encode_packet(&reply, secret).unwrap();
+        assert!(!bytes.windows(13).any(|w| w == b"nested secret"));
+        // a malformed value, 17 bytes of salt and hidden data
+        bytes.extend_from_slice(&[69, 20, 2, 0x80]);
+        bytes.extend_from_slice(&[0; 16]);
+        let len = bytes.len() as u16;
+        bytes[2..4].copy_from_slice(&len.to_be_bytes());
+
+        let mut decoded = h.load_bytes(&bytes).unwrap();
+        assert_eq!(h.decrypt_attributes(&mut decoded, &authenticator, secret), vec!["Tunnel-Password: Malformed salt encrypted value: 17 bytes"]);
+        let tunnel: Vec<_> = decoded.get_attributes_by_name("Tunnel-Password").collect();
+        assert_eq!((tunnel[0].is_hidden(), tunnel[0].get_value()), (false, &b"tunnel secret"[..]));
+        assert_eq!((tunnel[1].is_hidden(), tunnel[1].get_value().len()), (true, 17));
+        let nested = decoded.get_attribute_by_name("Test-Tlv").unwrap().get_child_by_name("Test-Secret").unwrap();
+        assert_eq!(nested.get_value(), b"nested secret");
+
+        // revealed values are not revealed twice
+        assert_eq!(h.decrypt_attributes(&mut decoded, &authenticator, secret).len(), 1);
+        assert_eq!(decoded.get_attribute_by_name("Tunnel-Password").unwrap().get_value(), b"tunnel secret");
+    }
+
+    #[cfg(feature = "vendor-microsoft")]
+    #[test]
+    fn mppe_keys() {
+        use crate::client::dictionary::Vendor;
+
+        let authenticator = vec![0x11; 16];
+        let h = Host::new(1812, 1813, 3799, Dictionary::default().with_vendor(Vendor::Microsoft).unwrap());
+        let key = vec![0x42; 32];
+
+        let mut reply = RadiusData::new(RadiusCode::AccessAccept, Some(1), Some(authenticator.clone()), None);
+        reply.add_attribute(h.create_attribute("MS-MPPE-Recv-Key", key.clone()).unwrap());
+
+        let mut decoded = h.load_bytes(&h.encode_packet(&reply, b"SECRET").unwrap()).unwrap();
+        assert_eq!(decoded.get_attributes()[0].get_value().len(), 2 + 48);
+        assert!(h.decrypt_attributes(&mut decoded, &authenticator, b"SECRET").is_empty());
+        assert_eq!(decoded.get_attribute_by_name("MS-MPPE-Recv-Key").unwrap().get_value(), key.as_slice());
+    }
+
+    #[test]
+    fn ports() {
+        let h = Host::new(1812, 1813, 3799, Dictionary::default());
+        assert_eq!(h.get_port(&RadiusCode::AccessRequest), 1812);
+        assert_eq!(h.get_port(&RadiusCode::AccountingRequest), 1813);
+        assert_eq!(h.get_port(&RadiusCode::CoaRequest), 3799);
+        assert_eq!(h.get_port(&RadiusCode::DisconnectRequest), 3799);
+    }
 }
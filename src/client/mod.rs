@@ -5,10 +5,12 @@ mod host;
 use std::io;
 use std::time::Duration;
 
-use super::radius::{RadiusCode, RadiusAttribute, RadiusAttributeDef, RadiusData};
+#[cfg(feature = "serde")]
+use super::radius::{DataEntry, EntryValue, from_hex};
+use super::radius::{RadiusCode, RadiusAttribute, RadiusAttributeDef, RadiusAttributeType, RadiusVendorDef, RadiusValueDef, RadiusData, RadiusEncryption, IntoRadiusValue, MAX_PACKET_LENGTH, MAX_VALUE_LENGTH, VENDOR_SPECIFIC, LONG_EXTENDED_MORE};
 
 use self::host::Host;
-use self::dictionary::Dictionary;
+use self::dictionary::{Dictionary, Precedence};
 
 use mio::net::UdpSocket;
 use mio::{Events, Ready, Poll, PollOpt, Token};
@@ -39,6 +41,13 @@ impl Client {
         })
     }
 
+    /// layers the definitions of another Dictionary on top of the client one
+    /// e.g. `Client::factory(.., Dictionary::default())?.with_dictionary(site, Precedence::Incoming)?`
+    pub fn with_dictionary(mut self, dict: Dictionary, precedence: Precedence) -> Result<Client, String> {
+        self.host.merge_dictionary(dict, precedence)?;
+        Ok(self)
+    }
+
     /// Creates a RadiusAttribute element starting from Dictionary's attribute name
     pub fn create_attribute_by_name(&self, name: &str, value: Vec<u8>) -> Result<RadiusAttribute, String> {
         self.host.create_attribute_by_name(name, value)
@@ -49,16 +58,55 @@ impl Client {
         self.host.create_attribute_by_code(code, value)
     }
 
+    /// Creates a RadiusAttribute element starting from Dictionary's attribute name and value name
+    pub fn create_attribute_by_value(&self, name: &str, value: &str) -> Result<RadiusAttribute, String> {
+        self.host.create_attribute_by_value(name, value)
+    }
+
+    /// Creates a RadiusAttribute element from a native value, encoded after the attribute data type
+    pub fn create_attribute<T: IntoRadiusValue>(&self, name: &str, value: T) -> Result<RadiusAttribute, String> {
+        self.host.create_attribute(name, value)
+    }
+
+    /// Creates a tagged RadiusAttribute element from a native value, e.g. for Tunnel-* attributes
+    pub fn create_tagged_attribute<T: IntoRadiusValue>(&self, name: &str, tag: u8, value: T) -> Result<RadiusAttribute, String> {
+        self.host.create_tagged_attribute(name, tag, value)
+    }
+
     /// generates an authentication request packet
+    /// User-Password is hidden and Message-Authenticator signed, so that the packet can be sent as is
     pub fn get_auth_packet(&self, username: &str, password: &str, identifier: Option<u8>, authenticator: Option<Vec<u8>>, attributes: Option<Vec<RadiusAttribute>>) -> Result<RadiusData, String> {
         self.host.get_auth_packet(username, password, self.secret.as_bytes(), identifier, authenticator, attributes)
     }
 
+    /// packet bytes as sent by `send_packet`
+    /// attributes flagged with encrypt=1 or encrypt=2 are hidden and Message-Authenticator signed,
+    /// `RadiusData::get_bytes` refusing values still in clear
+    pub fn encode_packet(&self, p: &RadiusData) -> Result<Vec<u8>, String> {
+        self.host.encode_packet(p, self.secret.as_bytes())
+    }
+
+    /// reveals the salt encrypted attributes (encrypt=2, e.g. Tunnel-Password) of a reply to a request
+    /// replies returned by `send_packet` are already revealed
+    /// fails listing the values that can't be revealed, which are left hidden, the others being revealed anyway
+    pub fn decrypt_attributes(&self, req: &RadiusData, reply: &mut RadiusData) -> Result<(), String> {
+        let errors = self.host.decrypt_attributes(reply, req.get_authenticator(), self.secret.as_bytes());
+        if errors.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(errors.join(", "))
+        }
+    }
+
     /// sends a packet to the RADIUS server
+    /// attributes flagged with encrypt=1 or encrypt=2 are hidden and Message-Authenticator signed on the way out
+    /// salt encrypted attributes of the reply are revealed, those that can't be being left hidden, see `RadiusAttribute::is_hidden`
     pub fn send_packet(&self, p: &RadiusData) -> Result<RadiusData, io::Error> {
         let local = "0.0.0.0:0".parse().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
         let remote = &format!("{}:{}", self.server, self.host.get_port(p.get_code())).parse().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
+        let bytes = self.encode_packet(p).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
         let socket = UdpSocket::bind(&local)?;
         self.poll.register(&socket, Token(0), Ready::readable(), PollOpt::edge())?;
 
@@ -70,7 +118,7 @@ impl Client {
                 break;
             }
 
-            socket.send_to(&p.get_bytes(), remote)?;
+            socket.send_to(&bytes, remote)?;
 
             self.poll.poll(&mut events, Some(timeout))?;
 
@@ -82,7 +130,7 @@ impl Client {
 
                         if amount > 0 {
                             let response = &response[0..amount];//shrink slice
-                            return self.verify_reply(&p, self.host.load_bytes(&response).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+                            return self.verify_reply(p, response);
                         }
                     },
                     _ => return Err(io::Error::new(io::ErrorKind::Other, "Invalid Token")),
@@ -95,16 +143,19 @@ impl Client {
         Err(io::Error::new(io::ErrorKind::TimedOut, ""))
     }
 
-    fn verify_reply(&self, req: &RadiusData, res: RadiusData) -> Result<RadiusData, io::Error> {
+    fn verify_reply(&self, req: &RadiusData, rawreply: &[u8]) -> Result<RadiusData, io::Error> {
+        // authenticator is verified against the raw reply as received
+        let mut res = self.host.load_bytes(rawreply).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        // padding beyond the header length is not covered by the authenticator
+        let rawreply = &rawreply[..(u16::from_be_bytes([rawreply[2], rawreply[3]]) as usize)];
         if req.get_identifier() != res.get_identifier() {
             return Err(io::Error::new(io::ErrorKind::InvalidData, String::from("Mismatching packet identifier")));
         }
 
-        let rawreply = res.get_bytes();
         let mut temp = Vec::new();
-        temp.append(&mut (&rawreply[0..4]).to_vec());
+        temp.append(&mut rawreply[0..4].to_vec());
         temp.append(&mut req.get_authenticator().to_vec());
-        temp.append(&mut (&rawreply[20..]).to_vec());
+        temp.append(&mut rawreply[20..].to_vec());
         temp.append(&mut self.secret.as_bytes().to_vec());
 
         let mut md5 = Md5::new();
@@ -113,6 +164,8 @@ impl Client {
         md5.result(&mut hash);
 
         if hash == res.get_authenticator() {
+            // an authenticated reply is kept whole, values that can't be revealed stay hidden
+            self.host.decrypt_attributes(&mut res, req.get_authenticator(), self.secret.as_bytes());
             Ok(res)
         }
         else {
@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use super::client::dictionary::Dictionary;
+use super::radius::{RadiusAttributeDef, RadiusAttributeType};
+
+/// generates typed Rust code from a dictionary file or directory, meant to be called by a build script
+///
+/// ```no_run
+/// // build.rs
+/// let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("radius.rs");
+/// radius_client::codegen::generate("dictionary", out).unwrap();
+/// ```
+///
+/// the generated file is then included in a module of the crate:
+/// `mod attributes { include!(concat!(env!("OUT_DIR"), "/radius.rs")); }`
+pub fn generate<P: AsRef<Path>, Q: AsRef<Path>>(dictionary: P, out: Q) -> Result<(), String> {
+    let dictionary = dictionary.as_ref();
+    let dict = if dictionary.is_dir() {
+        Dictionary::from_dir(dictionary)
+    }
+    else {
+        Dictionary::from_file(dictionary)
+    }.map_err(|e| e.to_string())?;
+
+    let out = out.as_ref();
+    fs::write(out, generate_source(&dict)?).map_err(|e| format!("{}: {}", out.display(), e))
+}
+
+/// generates typed Rust code for the attributes of a Dictionary:
+/// * an attribute name constant for every attribute, e.g. `USER_NAME`
+/// * an enum for every set of named values, e.g. `ServiceType`
+/// * a `DictionaryAttributes` trait with typed getters and setters implemented for RadiusData
+///
+/// attributes whose identifiers clash with a previous one are skipped, names without any
+/// alphanumeric character and values out of range for the attribute type are rejected
+pub fn generate_source(dict: &Dictionary) -> Result<String, String> {
+    let mut res = String::from("// generated by radius_client::codegen, do not edit\n");
+
+    let mut consts = HashSet::new();
+    for def in dict.get_attributes() {
+        let name = upper_snake(def.get_name())?;
+        if consts.insert(name.clone()) {
+            let _ = write!(res, "\n/// {} ({})\npub const {}: &str = \"{}\";\n", def.get_name(), def.get_oid(), name, def.get_name());
+        }
+    }
+
+    let mut enums = HashSet::new();
+    let mut accessors = Vec::new();
+    let mut methods = HashSet::new();
+    for def in dict.get_attributes() {
+        let enumeration = match def.get_type() {
+            RadiusAttributeType::Integer | RadiusAttributeType::Short | RadiusAttributeType::Byte if !dict.get_values(def.get_name()).is_empty() => {
+                let name = camel(def.get_name())?;
+                if enums.insert(name.clone()) {
+                    write_enum(&mut res, dict, def, &name)?;
+                    Some(name)
+                }
+                else {
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let method = lower_snake(def.get_name())?;
+        if !methods.insert(method.clone()) {
+            continue;
+        }
+        if let Some(accessor) = Accessor::new(def, enumeration) {
+            accessors.push((def, method, accessor));
+        }
+    }
+
+    res.push_str("\n/// typed accessors of the dictionary attributes\npub trait DictionaryAttributes {\n");
+    for (def, method, accessor) in accessors.iter() {
+        let _ = write!(res, "    /// retrieves {} ({})\n    fn get_{}(&self) -> ::std::option::Option<{}>;\n", def.get_name(), def.get_oid(), method, accessor.rtype);
+        let _ = write!(res, "    /// sets {} ({}), replacing the current value\n    fn set_{}(&mut self, dict: &::radius_client::client::dictionary::Dictionary, value: {}) -> ::std::result::Result<(), ::std::string::String>;\n", def.get_name(), def.get_oid(), method, accessor.rtype);
+    }
+    res.push_str("}\n\nimpl DictionaryAttributes for ::radius_client::radius::RadiusData {\n");
+    for (def, method, accessor) in accessors.iter() {
+        let _ = write!(res, "    fn get_{}(&self) -> ::std::option::Option<{}> {{\n        self.get_attribute_by_oid(\"{}\"){}\n    }}\n\n", method, accessor.rtype, def.get_oid(), accessor.decode);
+        let _ = write!(res, "    fn set_{}(&mut self, dict: &::radius_client::client::dictionary::Dictionary, value: {}) -> ::std::result::Result<(), ::std::string::String> {{\n        set_dictionary_attribute(self, dict, \"{}\", {})\n    }}\n\n", method, accessor.rtype, def.get_oid(), accessor.encode);
+    }
+    res.push_str("}\n");
+    res.push_str(SET_DICTIONARY_ATTRIBUTE);
+
+    Ok(res)
+}
+
+// replaces the value of an attribute, adding it when missing
+const SET_DICTIONARY_ATTRIBUTE: &str = r"
+fn set_dictionary_attribute(data: &mut ::radius_client::radius::RadiusData, dict: &::radius_client::client::dictionary::Dictionary, oid: &str, value: ::std::vec::Vec<u8>) -> ::std::result::Result<(), ::std::string::String> {
+    let attr = dict.create_attribute_by_oid(oid, value)?;
+    if let ::std::option::Option::Some(a) = data.get_attribute_by_oid_mut(oid) {
+        a.set_value(attr.get_value().to_vec());
+        return ::std::result::Result::Ok(());
+    }
+    data.add_nested_attribute(attr)
+}
+";
+
+fn write_enum(res: &mut String, dict: &Dictionary, def: &RadiusAttributeDef, name: &str) -> Result<(), String> {
+    let max = match def.get_type() {
+        RadiusAttributeType::Short => u32::from(u16::MAX),
+        RadiusAttributeType::Byte => u32::from(u8::MAX),
+        _ => u32::MAX,
+    };
+    let mut variants = HashSet::new();
+    let mut numbers = HashSet::new();
+    let mut values: Vec<(String, u32, bool)> = Vec::new();
+    for v in dict.get_values(def.get_name()) {
+        if v.get_value() > max {
+            return Err(format!("Value {} of {} out of range: {}, maximum {}", v.get_name(), def.get_name(), v.get_value(), max));
+        }
+        let variant = camel(v.get_name())?;
+        if variants.insert(variant.clone()) {
+            values.push((variant, v.get_value(), numbers.insert(v.get_value())));
+        }
+    }
+
+    let _ = write!(res, "\n/// {} values\n#[derive(Clone, Copy, PartialEq, Eq, Debug)]\npub enum {} {{\n", def.get_name(), name);
+    for (variant, value, _) in values.iter() {
+        let _ = writeln!(res, "    /// {}\n    {},", value, variant);
+    }
+    let _ = write!(res, "}}\n\nimpl {} {{\n    /// converts from the attribute number\n    pub fn from_u32(value: u32) -> ::std::option::Option<{}> {{\n        match value {{\n", name, name);
+    for (variant, value, _) in values.iter().filter(|(_, _, first)| *first) {
+        let _ = writeln!(res, "            {} => ::std::option::Option::Some({}::{}),", value, name, variant);
+    }
+    let _ = write!(res, "            _ => ::std::option::Option::None,\n        }}\n    }}\n\n    /// converts into the attribute number\n    pub fn to_u32(self) -> u32 {{\n        match self {{\n");
+    for (variant, value, _) in values.iter() {
+        let _ = writeln!(res, "            {}::{} => {},", name, variant, value);
+    }
+    res.push_str("        }\n    }\n}\n");
+    Ok(())
+}
+
+// Rust type of an attribute, with the conversions from the attribute option `a` and into the wire
+struct Accessor {
+    rtype: String,
+    decode: String,
+    encode: String,
+}
+
+impl Accessor {
+    fn new(def: &RadiusAttributeDef, enumeration: Option<String>) -> Option<Accessor> {
+        let number = |rtype: &str, size: usize| {
+            let decode = format!(".and_then(|a| <[u8; {}] as ::std::convert::TryFrom<&[u8]>>::try_from(a.get_value()).ok()).map({}::from_be_bytes)", size, rtype);
+            match enumeration {
+                Some(ref e) => Accessor {
+                    rtype: e.clone(),
+                    decode: match rtype {
+                        "u32" => format!("{}.and_then({}::from_u32)", decode, e),
+                        _ => format!("{}.and_then(|n| {}::from_u32(u32::from(n)))", decode, e),
+                    },
+                    encode: match rtype {
+                        "u32" => "value.to_u32().to_be_bytes().to_vec()".to_owned(),
+                        // values are range checked by write_enum
+                        _ => format!("(value.to_u32() as {}).to_be_bytes().to_vec()", rtype),
+                    },
+                },
+                None => Accessor {
+                    rtype: rtype.to_owned(),
+                    decode,
+                    encode: "value.to_be_bytes().to_vec()".to_owned(),
+                },
+            }
+        };
+        let address = |rtype: &str, size: usize| Accessor {
+            rtype: rtype.to_owned(),
+            decode: format!(".and_then(|a| <[u8; {}] as ::std::convert::TryFrom<&[u8]>>::try_from(a.get_value()).ok()).map({}::from)", size, rtype),
+            encode: "value.octets().to_vec()".to_owned(),
+        };
+
+        let res = match def.get_type() {
+            RadiusAttributeType::String => Accessor {
+                rtype: "::std::string::String".to_owned(),
+                decode: ".map(|a| ::std::string::String::from_utf8_lossy(a.get_value()).into_owned())".to_owned(),
+                encode: "value.into_bytes()".to_owned(),
+            },
+            RadiusAttributeType::Integer | RadiusAttributeType::Date => number("u32", 4),
+            RadiusAttributeType::Integer64 => number("u64", 8),
+            RadiusAttributeType::Short => number("u16", 2),
+            RadiusAttributeType::Byte => number("u8", 1),
+            RadiusAttributeType::Signed => number("i32", 4),
+            RadiusAttributeType::IpAddr => address("::std::net::Ipv4Addr", 4),
+            RadiusAttributeType::Ipv6Addr => address("::std::net::Ipv6Addr", 16),
+            RadiusAttributeType::Tlv | RadiusAttributeType::Vsa | RadiusAttributeType::Evs | RadiusAttributeType::Extended | RadiusAttributeType::LongExtended => return None,
+            _ => Accessor {
+                rtype: "::std::vec::Vec<u8>".to_owned(),
+                decode: ".map(|a| a.get_value().to_vec())".to_owned(),
+                encode: "value".to_owned(),
+            },
+        };
+        Some(res)
+    }
+}
+
+// splits a dictionary name in its alphanumeric words
+fn words(name: &str) -> Result<Vec<&str>, String> {
+    let res: Vec<&str> = name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    if res.is_empty() {
+        return Err(format!("No identifier for {}: no alphanumeric characters", name));
+    }
+    Ok(res)
+}
+
+// identifiers can't start with a digit, e.g. 3GPP-IMSI
+fn identifier(s: String) -> String {
+    match s.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{}", s),
+        _ if s == "Self" => format!("{}_", s),
+        _ => s,
+    }
+}
+
+fn upper_snake(name: &str) -> Result<String, String> {
+    Ok(identifier(words(name)?.join("_").to_ascii_uppercase()))
+}
+
+fn lower_snake(name: &str) -> Result<String, String> {
+    Ok(words(name)?.join("_").to_ascii_lowercase())
+}
+
+fn camel(name: &str) -> Result<String, String> {
+    Ok(identifier(words(name)?.iter().map(|w| {
+        let lower = w.to_ascii_lowercase();
+        lower[..1].to_ascii_uppercase() + &lower[1..]
+    }).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_source;
+    use super::super::client::dictionary::Dictionary;
+    use std::str::FromStr;
+
+    #[test]
+    fn source() {
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   User-Name       1   string
+ATTRIBUTE   Service-Type    6   integer
+ATTRIBUTE   Framed-IP-Address   8   ipaddr
+VALUE   Service-Type    Login-User  1
+VALUE   Service-Type    Framed-User 2
+VALUE   Service-Type    Framed      2
+VENDOR      3GPP    10415
+BEGIN-VENDOR    3GPP
+ATTRIBUTE   3GPP-IMSI   1   string
+END-VENDOR      3GPP
+").unwrap();
+        let source = generate_source(&d).unwrap();
+
+        assert!(source.contains("pub const USER_NAME: &str = \"User-Name\";"));
+        assert!(source.contains("pub const _3GPP_IMSI: &str = \"3GPP-IMSI\";"));
+        assert!(source.contains("pub enum ServiceType {"));
+        assert!(source.contains("2 => ::std::option::Option::Some(ServiceType::FramedUser),"));
+        assert!(!source.contains("2 => ::std::option::Option::Some(ServiceType::Framed),"));
+        assert!(source.contains("ServiceType::Framed => 2,"));
+        assert!(source.contains("fn get_service_type(&self) -> ::std::option::Option<ServiceType>;"));
+        assert!(source.contains("fn set_framed_ip_address(&mut self, dict: &::radius_client::client::dictionary::Dictionary, value: ::std::net::Ipv4Addr)"));
+        assert!(source.contains("self.get_attribute_by_oid(\"26.10415.1\")"));
+    }
+
+    #[test]
+    fn invalid() {
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   Test-Short  1   short
+ATTRIBUTE   Test-Byte   2   byte
+VALUE   Test-Short  Big     65535
+VALUE   Test-Byte   Small   255
+").unwrap();
+        assert!(generate_source(&d).is_ok());
+
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   Test-Short  1   short
+VALUE   Test-Short  Too-Big 65536
+").unwrap();
+        assert_eq!(generate_source(&d), Err(String::from("Value Too-Big of Test-Short out of range: 65536, maximum 65535")));
+
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   Test-Byte   2   byte
+VALUE   Test-Byte   Too-Big 256
+").unwrap();
+        assert_eq!(generate_source(&d), Err(String::from("Value Too-Big of Test-Byte out of range: 256, maximum 255")));
+
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   ---     1   string
+").unwrap();
+        assert_eq!(generate_source(&d), Err(String::from("No identifier for ---: no alphanumeric characters")));
+
+        let d = Dictionary::from_str(r"
+ATTRIBUTE   Test-Int    1   integer
+VALUE   Test-Int    _   1
+").unwrap();
+        assert_eq!(generate_source(&d), Err(String::from("No identifier for _: no alphanumeric characters")));
+    }
+}
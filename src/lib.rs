@@ -14,3 +14,5 @@ extern crate mio;
 pub mod client;
 /// Protocol entities
 pub mod radius;
+/// Typed code generation from dictionaries, for build scripts
+pub mod codegen;
@@ -1,12 +1,273 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
-#[derive(Clone)]
+/// Maximum length of an attribute value
+pub const MAX_VALUE_LENGTH: usize = 253;
+
+/// RADIUS attribute data type (RFC 8044)
+#[derive(Clone, PartialEq, Debug)]
 pub enum RadiusAttributeType {
+    /// UTF-8 text
     String,
+    /// 32 bit unsigned value
     Integer,
+    /// raw bytes
     Octets,
+    /// IPv4 address
     IpAddr,
+    /// 32 bit seconds since epoch
     Date,
+    /// IPv6 address
+    Ipv6Addr,
+    /// IPv6 prefix, reserved byte, prefix length and up to 16 bytes of prefix
+    Ipv6Prefix,
+    /// IPv4 prefix, reserved byte, prefix length and 4 bytes of prefix
+    Ipv4Prefix,
+    /// 64 bit IPv6 interface identifier
+    IfId,
+    /// 64 bit unsigned value
+    Integer64,
+    /// 8 bit unsigned value
+    Byte,
+    /// 16 bit unsigned value
+    Short,
+    /// 32 bit signed value
+    Signed,
+    /// 48 bit Ethernet MAC address
+    Ether,
+    /// IPv4 or IPv6 address, depending on length
+    ComboIp,
+    /// Ascend binary filter
+    ABinary,
+    /// nested type-length-value attributes
+    Tlv,
+    /// Vendor-Specific content: vendor id followed by vendor data
+    Vsa,
+    /// Extended-Vendor-Specific content: vendor id, vendor type and vendor data
+    Evs,
+    /// RFC 6929 extended attribute space: extended type followed by its value
+    Extended,
+    /// RFC 6929 long extended attribute space: extended type, flags and fragmented value
+    LongExtended,
+}
+
+impl RadiusAttributeType {
+    /// dictionary name of the data type
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RadiusAttributeType::String => "string",
+            RadiusAttributeType::Integer => "integer",
+            RadiusAttributeType::Octets => "octets",
+            RadiusAttributeType::IpAddr => "ipaddr",
+            RadiusAttributeType::Date => "date",
+            RadiusAttributeType::Ipv6Addr => "ipv6addr",
+            RadiusAttributeType::Ipv6Prefix => "ipv6prefix",
+            RadiusAttributeType::Ipv4Prefix => "ipv4prefix",
+            RadiusAttributeType::IfId => "ifid",
+            RadiusAttributeType::Integer64 => "integer64",
+            RadiusAttributeType::Byte => "byte",
+            RadiusAttributeType::Short => "short",
+            RadiusAttributeType::Signed => "signed",
+            RadiusAttributeType::Ether => "ether",
+            RadiusAttributeType::ComboIp => "combo-ip",
+            RadiusAttributeType::ABinary => "abinary",
+            RadiusAttributeType::Tlv => "tlv",
+            RadiusAttributeType::Vsa => "vsa",
+            RadiusAttributeType::Evs => "evs",
+            RadiusAttributeType::Extended => "extended",
+            RadiusAttributeType::LongExtended => "long-extended",
+        }
+    }
+
+    /// size in bytes of fixed length types
+    pub fn get_size(&self) -> Option<usize> {
+        match self {
+            RadiusAttributeType::Byte => Some(1),
+            RadiusAttributeType::Short => Some(2),
+            RadiusAttributeType::Integer | RadiusAttributeType::Signed | RadiusAttributeType::IpAddr | RadiusAttributeType::Date => Some(4),
+            RadiusAttributeType::Ether | RadiusAttributeType::Ipv4Prefix => Some(6),
+            RadiusAttributeType::Integer64 | RadiusAttributeType::IfId => Some(8),
+            RadiusAttributeType::Ipv6Addr => Some(16),
+            _ => None,
+        }
+    }
+
+    /// largest value of the data type, None when only bound by the attribute carrying it
+    pub fn get_max_size(&self) -> Option<usize> {
+        match self {
+            RadiusAttributeType::Ipv6Prefix => Some(18),
+            RadiusAttributeType::ComboIp => Some(16),
+            _ => self.get_size(),
+        }
+    }
+
+    /// checks that a wire value is well formed for this data type
+    /// the value length is only checked against the data type, values of concat and long extended
+    /// attributes spanning several attributes
+    pub fn validate(&self, value: &[u8]) -> Result<(), String> {
+        if let Some(size) = self.get_size() {
+            if value.len() != size {
+                return Err(format!("Invalid length for {}: {} bytes, expected {}", self.get_name(), value.len(), size));
+            }
+        }
+
+        match self {
+            RadiusAttributeType::Ipv6Prefix if value.len() < 2 || value.len() > 18 || value[1] > 128 || value.len() - 2 < (value[1] as usize + 7) / 8 => {
+                return Err(format!("Malformed ipv6prefix: {:?}", value));
+            },
+            RadiusAttributeType::Ipv4Prefix if value[1] > 32 => {
+                return Err(format!("Malformed ipv4prefix: {:?}", value));
+            },
+            RadiusAttributeType::ComboIp if value.len() != 4 && value.len() != 16 => {
+                return Err(format!("Invalid length for combo-ip: {} bytes", value.len()));
+            },
+            RadiusAttributeType::Vsa if value.len() < 4 => {
+                return Err(format!("Invalid length for vsa: {} bytes", value.len()));
+            },
+            RadiusAttributeType::Evs if value.len() < 5 => {
+                return Err(format!("Invalid length for evs: {} bytes", value.len()));
+            },
+            RadiusAttributeType::Extended if value.is_empty() => {
+                return Err(String::from("Invalid length for extended: 0 bytes"));
+            },
+            RadiusAttributeType::LongExtended if value.len() < 2 => {
+                return Err(format!("Invalid length for long-extended: {} bytes", value.len()));
+            },
+            RadiusAttributeType::Tlv => {
+                let mut i = 0;
+                while i < value.len() {
+                    if i + 2 > value.len() || value[i + 1] < 2 || i + value[i + 1] as usize > value.len() {
+                        return Err(format!("Malformed tlv at offset {}", i));
+                    }
+                    i += value[i + 1] as usize;
+                }
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    /// converts a textual representation into the wire value
+    pub fn encode(&self, s: &str) -> Result<Vec<u8>, String> {
+        let err = || format!("Malformed {} value: {}", self.get_name(), s);
+        let res = match self {
+            RadiusAttributeType::String => s.as_bytes().to_vec(),
+            RadiusAttributeType::Integer | RadiusAttributeType::Date => s.parse::<u32>().map_err(|_| err())?.to_be_bytes().to_vec(),
+            RadiusAttributeType::Integer64 => s.parse::<u64>().map_err(|_| err())?.to_be_bytes().to_vec(),
+            RadiusAttributeType::Byte => vec![s.parse::<u8>().map_err(|_| err())?],
+            RadiusAttributeType::Short => s.parse::<u16>().map_err(|_| err())?.to_be_bytes().to_vec(),
+            RadiusAttributeType::Signed => s.parse::<i32>().map_err(|_| err())?.to_be_bytes().to_vec(),
+            RadiusAttributeType::IpAddr => s.parse::<Ipv4Addr>().map_err(|_| err())?.octets().to_vec(),
+            RadiusAttributeType::Ipv6Addr => s.parse::<Ipv6Addr>().map_err(|_| err())?.octets().to_vec(),
+            RadiusAttributeType::ComboIp => match s.parse::<Ipv4Addr>() {
+                Ok(ip) => ip.octets().to_vec(),
+                Err(_) => s.parse::<Ipv6Addr>().map_err(|_| err())?.octets().to_vec(),
+            },
+            RadiusAttributeType::Ipv6Prefix => {
+                let mut parts = s.splitn(2, '/');
+                let ip = parts.next().unwrap_or("").parse::<Ipv6Addr>().map_err(|_| err())?;
+                let len = parts.next().unwrap_or("128").parse::<u8>().map_err(|_| err())?;
+                if len > 128 {
+                    return Err(err());
+                }
+                let mut res = vec![0, len];
+                res.extend_from_slice(&ip.octets()[..(len as usize + 7) / 8]);
+                res
+            },
+            RadiusAttributeType::Ipv4Prefix => {
+                let mut parts = s.splitn(2, '/');
+                let ip = parts.next().unwrap_or("").parse::<Ipv4Addr>().map_err(|_| err())?;
+                let len = parts.next().unwrap_or("32").parse::<u8>().map_err(|_| err())?;
+                let mut res = vec![0, len];
+                res.extend_from_slice(&ip.octets());
+                res
+            },
+            RadiusAttributeType::IfId => Self::parse_hex_groups(s, ':', 2, 4).ok_or_else(err)?,
+            RadiusAttributeType::Ether => Self::parse_hex_groups(s, ':', 1, 6).ok_or_else(err)?,
+            RadiusAttributeType::Octets | RadiusAttributeType::ABinary | RadiusAttributeType::Tlv
+                | RadiusAttributeType::Vsa | RadiusAttributeType::Evs
+                | RadiusAttributeType::Extended | RadiusAttributeType::LongExtended => {
+                if s.starts_with("0x") || s.starts_with("0X") {
+                    Self::parse_hex(&s[2..]).ok_or_else(err)?
+                }
+                else {
+                    s.as_bytes().to_vec()
+                }
+            },
+        };
+
+        self.validate(&res)?;
+        Ok(res)
+    }
+
+    /// converts a wire value into its textual representation
+    pub fn decode(&self, value: &[u8]) -> Result<String, String> {
+        self.validate(value)?;
+
+        Ok(match self {
+            RadiusAttributeType::String => String::from_utf8(value.to_vec()).map_err(|e| format!("Malformed string value: {}", e))?,
+            RadiusAttributeType::Integer | RadiusAttributeType::Date => Self::read_be(value).to_string(),
+            RadiusAttributeType::Integer64 | RadiusAttributeType::Byte | RadiusAttributeType::Short => Self::read_be(value).to_string(),
+            RadiusAttributeType::Signed => (Self::read_be(value) as u32 as i32).to_string(),
+            RadiusAttributeType::IpAddr => Ipv4Addr::new(value[0], value[1], value[2], value[3]).to_string(),
+            RadiusAttributeType::Ipv6Addr => Self::ipv6(value).to_string(),
+            RadiusAttributeType::ComboIp => if value.len() == 4 {
+                Ipv4Addr::new(value[0], value[1], value[2], value[3]).to_string()
+            }
+            else {
+                Self::ipv6(value).to_string()
+            },
+            RadiusAttributeType::Ipv6Prefix => {
+                let mut octets = [0u8; 16];
+                let len = value.len() - 2;
+                octets[..len].copy_from_slice(&value[2..]);
+                format!("{}/{}", Ipv6Addr::from(octets), value[1])
+            },
+            RadiusAttributeType::Ipv4Prefix => format!("{}/{}", Ipv4Addr::new(value[2], value[3], value[4], value[5]), value[1]),
+            RadiusAttributeType::IfId => value.chunks(2).map(|c| format!("{:02x}{:02x}", c[0], c[1])).collect::<Vec<String>>().join(":"),
+            RadiusAttributeType::Ether => value.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(":"),
+            RadiusAttributeType::Octets | RadiusAttributeType::ABinary | RadiusAttributeType::Tlv
+                | RadiusAttributeType::Vsa | RadiusAttributeType::Evs
+                | RadiusAttributeType::Extended | RadiusAttributeType::LongExtended => {
+                format!("0x{}", value.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+            },
+        })
+    }
+
+    fn read_be(data: &[u8]) -> u64 {
+        data.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b))
+    }
+
+    fn ipv6(value: &[u8]) -> Ipv6Addr {
+        let mut octets = [0u8; 16];
+        octets.copy_from_slice(value);
+        Ipv6Addr::from(octets)
+    }
+
+    fn parse_hex(s: &str) -> Option<Vec<u8>> {
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return None;
+        }
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..(i + 2)], 16).ok()).collect()
+    }
+
+    // parses "aa:bb:..." or "aabb:ccdd:..." notations, made of `groups` groups of `width` bytes
+    fn parse_hex_groups(s: &str, separator: char, width: usize, groups: usize) -> Option<Vec<u8>> {
+        let parts: Vec<&str> = s.split(separator).collect();
+        if parts.len() != groups {
+            return None;
+        }
+
+        let mut res = Vec::new();
+        for p in parts {
+            if p.len() != width * 2 {
+                return None;
+            }
+            res.append(&mut Self::parse_hex(p)?);
+        }
+        Some(res)
+    }
 }
 
 impl FromStr for RadiusAttributeType {
@@ -20,7 +281,65 @@ impl FromStr for RadiusAttributeType {
             "octets" => Ok(RadiusAttributeType::Octets),
             "ipaddr" => Ok(RadiusAttributeType::IpAddr),
             "date" => Ok(RadiusAttributeType::Date),
+            "ipv6addr" => Ok(RadiusAttributeType::Ipv6Addr),
+            "ipv6prefix" => Ok(RadiusAttributeType::Ipv6Prefix),
+            "ipv4prefix" => Ok(RadiusAttributeType::Ipv4Prefix),
+            "ifid" => Ok(RadiusAttributeType::IfId),
+            "integer64" => Ok(RadiusAttributeType::Integer64),
+            "byte" => Ok(RadiusAttributeType::Byte),
+            "short" => Ok(RadiusAttributeType::Short),
+            "signed" => Ok(RadiusAttributeType::Signed),
+            "ether" => Ok(RadiusAttributeType::Ether),
+            "combo-ip" => Ok(RadiusAttributeType::ComboIp),
+            "abinary" => Ok(RadiusAttributeType::ABinary),
+            "tlv" => Ok(RadiusAttributeType::Tlv),
+            "vsa" => Ok(RadiusAttributeType::Vsa),
+            "evs" => Ok(RadiusAttributeType::Evs),
+            "extended" => Ok(RadiusAttributeType::Extended),
+            "long-extended" => Ok(RadiusAttributeType::LongExtended),
             _ => Err(format!("Unrecognized Attribute type: {}", s)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RadiusAttributeType;
+    use std::str::FromStr;
+
+    #[test]
+    fn encode_decode() {
+        let cases = [
+            ("ipv6addr", "2001:db8::1", vec![0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+            ("ipv6prefix", "2001:db8::/32", vec![0, 32, 0x20, 0x01, 0x0d, 0xb8]),
+            ("ipv4prefix", "10.1.0.0/16", vec![0, 16, 10, 1, 0, 0]),
+            ("ifid", "0011:2233:4455:6677", vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77]),
+            ("integer64", "4294967296", vec![0, 0, 0, 1, 0, 0, 0, 0]),
+            ("byte", "7", vec![7]),
+            ("short", "258", vec![1, 2]),
+            ("signed", "-2", vec![0xff, 0xff, 0xff, 0xfe]),
+            ("ether", "00:11:22:aa:bb:cc", vec![0x00, 0x11, 0x22, 0xaa, 0xbb, 0xcc]),
+            ("combo-ip", "10.0.0.1", vec![10, 0, 0, 1]),
+            ("octets", "0x0102", vec![1, 2]),
+        ];
+
+        for (name, text, bytes) in cases.iter() {
+            let t = RadiusAttributeType::from_str(name).unwrap();
+            assert_eq!(&t.encode(text).unwrap(), bytes, "{}", name);
+            assert_eq!(&t.decode(bytes).unwrap(), text, "{}", name);
+        }
+    }
+
+    #[test]
+    fn validate() {
+        assert!(RadiusAttributeType::Integer.validate(&[0]).is_err());
+        assert!(RadiusAttributeType::Ipv6Prefix.validate(&[0, 64, 1]).is_err());
+        assert!(RadiusAttributeType::Ipv4Prefix.validate(&[0, 33, 10, 0, 0, 0]).is_err());
+        assert!(RadiusAttributeType::Ipv4Prefix.validate(&[0, 8, 10]).is_err());
+        assert!(RadiusAttributeType::ComboIp.validate(&[1, 2, 3, 4, 5]).is_err());
+        assert!(RadiusAttributeType::Tlv.validate(&[1, 3, 0, 2, 0]).is_err());
+        // concat values span several attributes
+        assert!(RadiusAttributeType::Octets.validate(&[0; 254]).is_ok());
+        assert!(RadiusAttributeType::Evs.validate(&[0, 0, 0, 9, 1]).is_ok());
+    }
+}
@@ -0,0 +1,345 @@
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::atype::RadiusAttributeType;
+
+/// RADIUS attribute value, typed after the data type of its definition
+#[derive(Clone, PartialEq, Debug)]
+pub enum RadiusValue {
+    /// string
+    String(String),
+    /// byte
+    U8(u8),
+    /// short
+    U16(u16),
+    /// integer
+    U32(u32),
+    /// integer64
+    U64(u64),
+    /// signed
+    I32(i32),
+    /// ipaddr, or combo-ip holding an IPv4 address
+    Ipv4Addr(Ipv4Addr),
+    /// ipv6addr, or combo-ip holding an IPv6 address
+    Ipv6Addr(Ipv6Addr),
+    /// ipv6prefix, address and prefix length
+    Ipv6Prefix(Ipv6Addr, u8),
+    /// ipv4prefix, address and prefix length
+    Ipv4Prefix(Ipv4Addr, u8),
+    /// ifid
+    IfId([u8; 8]),
+    /// ether
+    Ether([u8; 6]),
+    /// date
+    Date(SystemTime),
+    /// octets and every other type without a native representation
+    Bytes(Vec<u8>),
+}
+
+impl RadiusValue {
+    /// decodes a wire value according to the data type
+    pub fn decode(atype: &RadiusAttributeType, value: &[u8]) -> Result<RadiusValue, String> {
+        atype.validate(value)?;
+
+        let mut octets = [0u8; 16];
+        Ok(match atype {
+            RadiusAttributeType::String => RadiusValue::String(String::from_utf8(value.to_vec()).map_err(|e| format!("Malformed string value: {}", e))?),
+            RadiusAttributeType::Byte => RadiusValue::U8(value[0]),
+            RadiusAttributeType::Short => RadiusValue::U16(u16::from_be_bytes([value[0], value[1]])),
+            RadiusAttributeType::Integer => RadiusValue::U32(u32::from_be_bytes([value[0], value[1], value[2], value[3]])),
+            RadiusAttributeType::Signed => RadiusValue::I32(i32::from_be_bytes([value[0], value[1], value[2], value[3]])),
+            RadiusAttributeType::Integer64 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(value);
+                RadiusValue::U64(u64::from_be_bytes(bytes))
+            },
+            RadiusAttributeType::Date => RadiusValue::Date(UNIX_EPOCH + Duration::from_secs(u64::from(u32::from_be_bytes([value[0], value[1], value[2], value[3]])))),
+            RadiusAttributeType::IpAddr => RadiusValue::Ipv4Addr(Ipv4Addr::new(value[0], value[1], value[2], value[3])),
+            RadiusAttributeType::ComboIp if value.len() == 4 => RadiusValue::Ipv4Addr(Ipv4Addr::new(value[0], value[1], value[2], value[3])),
+            RadiusAttributeType::Ipv6Addr | RadiusAttributeType::ComboIp => {
+                octets.copy_from_slice(value);
+                RadiusValue::Ipv6Addr(Ipv6Addr::from(octets))
+            },
+            RadiusAttributeType::Ipv6Prefix => {
+                octets[..(value.len() - 2)].copy_from_slice(&value[2..]);
+                RadiusValue::Ipv6Prefix(Ipv6Addr::from(octets), value[1])
+            },
+            RadiusAttributeType::Ipv4Prefix => RadiusValue::Ipv4Prefix(Ipv4Addr::new(value[2], value[3], value[4], value[5]), value[1]),
+            RadiusAttributeType::IfId => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(value);
+                RadiusValue::IfId(bytes)
+            },
+            RadiusAttributeType::Ether => {
+                let mut bytes = [0u8; 6];
+                bytes.copy_from_slice(value);
+                RadiusValue::Ether(bytes)
+            },
+            _ => RadiusValue::Bytes(value.to_vec()),
+        })
+    }
+
+    /// wire representation of the value
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            RadiusValue::String(s) => s.as_bytes().to_vec(),
+            RadiusValue::U8(n) => vec![*n],
+            RadiusValue::U16(n) => n.to_be_bytes().to_vec(),
+            RadiusValue::U32(n) => n.to_be_bytes().to_vec(),
+            RadiusValue::U64(n) => n.to_be_bytes().to_vec(),
+            RadiusValue::I32(n) => n.to_be_bytes().to_vec(),
+            RadiusValue::Ipv4Addr(ip) => ip.octets().to_vec(),
+            RadiusValue::Ipv6Addr(ip) => ip.octets().to_vec(),
+            RadiusValue::Ipv6Prefix(ip, len) => {
+                let mut res = vec![0, *len];
+                res.extend_from_slice(&ip.octets()[..(((usize::from(*len) + 7) / 8).min(16))]);
+                res
+            },
+            RadiusValue::Ipv4Prefix(ip, len) => {
+                let mut res = vec![0, *len];
+                res.extend_from_slice(&ip.octets());
+                res
+            },
+            RadiusValue::IfId(bytes) => bytes.to_vec(),
+            RadiusValue::Ether(bytes) => bytes.to_vec(),
+            // dates beyond the 32 bit range are clamped
+            RadiusValue::Date(time) => {
+                let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+                (secs.min(u64::from(u32::MAX)) as u32).to_be_bytes().to_vec()
+            },
+            RadiusValue::Bytes(bytes) => bytes.clone(),
+        }
+    }
+
+    /// data type name of the value, as used in error messages
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            RadiusValue::String(_) => "string",
+            RadiusValue::U8(_) => "byte",
+            RadiusValue::U16(_) => "short",
+            RadiusValue::U32(_) => "integer",
+            RadiusValue::U64(_) => "integer64",
+            RadiusValue::I32(_) => "signed",
+            RadiusValue::Ipv4Addr(_) => "ipaddr",
+            RadiusValue::Ipv6Addr(_) => "ipv6addr",
+            RadiusValue::Ipv6Prefix(_, _) => "ipv6prefix",
+            RadiusValue::Ipv4Prefix(_, _) => "ipv4prefix",
+            RadiusValue::IfId(_) => "ifid",
+            RadiusValue::Ether(_) => "ether",
+            RadiusValue::Date(_) => "date",
+            RadiusValue::Bytes(_) => "octets",
+        }
+    }
+}
+
+/// native Rust values convertible into the typed value of an attribute data type
+pub trait IntoRadiusValue {
+    /// converts into a value of the data type, failing when it doesn't fit
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String>;
+}
+
+fn mismatch(value: &str, atype: &RadiusAttributeType) -> String {
+    format!("Cannot convert {} into {}", value, atype.get_name())
+}
+
+impl IntoRadiusValue for RadiusValue {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match (&self, atype) {
+            (RadiusValue::Bytes(_), _) => self.encode().into_radius_value(atype),
+            _ if RadiusValue::decode(atype, &self.encode()).map(|v| v.get_type_name() == self.get_type_name()).unwrap_or(false) => Ok(self),
+            _ => Err(mismatch(self.get_type_name(), atype)),
+        }
+    }
+}
+
+impl IntoRadiusValue for u32 {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        let err = |_| format!("Value out of range for {}: {}", atype.get_name(), self);
+        Ok(match atype {
+            RadiusAttributeType::Integer => RadiusValue::U32(self),
+            RadiusAttributeType::Short => RadiusValue::U16(u16::try_from(self).map_err(err)?),
+            RadiusAttributeType::Byte => RadiusValue::U8(u8::try_from(self).map_err(err)?),
+            RadiusAttributeType::Integer64 => RadiusValue::U64(u64::from(self)),
+            RadiusAttributeType::Signed => RadiusValue::I32(i32::try_from(self).map_err(err)?),
+            RadiusAttributeType::Date => RadiusValue::Date(UNIX_EPOCH + Duration::from_secs(u64::from(self))),
+            _ => return Err(mismatch("integer", atype)),
+        })
+    }
+}
+
+impl IntoRadiusValue for u8 {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        u32::from(self).into_radius_value(atype)
+    }
+}
+
+impl IntoRadiusValue for u16 {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        u32::from(self).into_radius_value(atype)
+    }
+}
+
+impl IntoRadiusValue for u64 {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::Integer64 => Ok(RadiusValue::U64(self)),
+            _ => u32::try_from(self).map_err(|_| format!("Value out of range for {}: {}", atype.get_name(), self))?.into_radius_value(atype),
+        }
+    }
+}
+
+impl IntoRadiusValue for i32 {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::Signed => Ok(RadiusValue::I32(self)),
+            _ => u32::try_from(self).map_err(|_| format!("Value out of range for {}: {}", atype.get_name(), self))?.into_radius_value(atype),
+        }
+    }
+}
+
+impl IntoRadiusValue for &str {
+    /// strings are parsed according to the data type, e.g. "10.0.0.1" for ipaddr
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::String => Ok(RadiusValue::String(self.to_owned())),
+            RadiusAttributeType::Octets => Ok(RadiusValue::Bytes(self.as_bytes().to_vec())),
+            _ => RadiusValue::decode(atype, &atype.encode(self)?),
+        }
+    }
+}
+
+impl IntoRadiusValue for String {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        self.as_str().into_radius_value(atype)
+    }
+}
+
+impl IntoRadiusValue for Ipv4Addr {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::IpAddr | RadiusAttributeType::ComboIp => Ok(RadiusValue::Ipv4Addr(self)),
+            RadiusAttributeType::Ipv4Prefix => Ok(RadiusValue::Ipv4Prefix(self, 32)),
+            _ => Err(mismatch("ipaddr", atype)),
+        }
+    }
+}
+
+impl IntoRadiusValue for Ipv6Addr {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::Ipv6Addr | RadiusAttributeType::ComboIp => Ok(RadiusValue::Ipv6Addr(self)),
+            RadiusAttributeType::Ipv6Prefix => Ok(RadiusValue::Ipv6Prefix(self, 128)),
+            _ => Err(mismatch("ipv6addr", atype)),
+        }
+    }
+}
+
+impl IntoRadiusValue for IpAddr {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match self {
+            IpAddr::V4(ip) => ip.into_radius_value(atype),
+            IpAddr::V6(ip) => ip.into_radius_value(atype),
+        }
+    }
+}
+
+impl IntoRadiusValue for (Ipv6Addr, u8) {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::Ipv6Prefix if self.1 <= 128 => Ok(RadiusValue::Ipv6Prefix(self.0, self.1)),
+            RadiusAttributeType::Ipv6Prefix => Err(format!("Invalid ipv6prefix length: {}", self.1)),
+            _ => Err(mismatch("ipv6prefix", atype)),
+        }
+    }
+}
+
+impl IntoRadiusValue for (Ipv4Addr, u8) {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype {
+            RadiusAttributeType::Ipv4Prefix if self.1 <= 32 => Ok(RadiusValue::Ipv4Prefix(self.0, self.1)),
+            RadiusAttributeType::Ipv4Prefix => Err(format!("Invalid ipv4prefix length: {}", self.1)),
+            _ => Err(mismatch("ipv4prefix", atype)),
+        }
+    }
+}
+
+impl IntoRadiusValue for SystemTime {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        let secs = self.duration_since(UNIX_EPOCH).map_err(|_| String::from("Date before the epoch"))?.as_secs();
+        match atype {
+            RadiusAttributeType::Date if secs <= u64::from(u32::MAX) => Ok(RadiusValue::Date(self)),
+            RadiusAttributeType::Date => Err(format!("Date out of range: {} seconds", secs)),
+            _ => Err(mismatch("date", atype)),
+        }
+    }
+}
+
+impl IntoRadiusValue for &[u8] {
+    /// raw bytes must be well formed for fixed size types
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        match atype.get_size() {
+            Some(_) => RadiusValue::decode(atype, self),
+            None if *atype == RadiusAttributeType::String => RadiusValue::decode(atype, self),
+            // length limits of variable size types depend on the attribute, checked by the caller
+            None => Ok(RadiusValue::Bytes(self.to_vec())),
+        }
+    }
+}
+
+impl IntoRadiusValue for Vec<u8> {
+    fn into_radius_value(self, atype: &RadiusAttributeType) -> Result<RadiusValue, String> {
+        self.as_slice().into_radius_value(atype)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntoRadiusValue, RadiusValue};
+    use super::super::atype::RadiusAttributeType;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn decode_encode() {
+        let cases = [
+            (RadiusAttributeType::String, vec![0x61, 0x62], RadiusValue::String("ab".to_owned())),
+            (RadiusAttributeType::Integer, vec![0, 0, 1, 2], RadiusValue::U32(258)),
+            (RadiusAttributeType::Short, vec![1, 2], RadiusValue::U16(258)),
+            (RadiusAttributeType::Signed, vec![0xff, 0xff, 0xff, 0xfe], RadiusValue::I32(-2)),
+            (RadiusAttributeType::IpAddr, vec![10, 0, 0, 1], RadiusValue::Ipv4Addr(Ipv4Addr::new(10, 0, 0, 1))),
+            (RadiusAttributeType::ComboIp, vec![10, 0, 0, 1], RadiusValue::Ipv4Addr(Ipv4Addr::new(10, 0, 0, 1))),
+            (RadiusAttributeType::Ipv6Prefix, vec![0, 32, 0x20, 0x01, 0x0d, 0xb8], RadiusValue::Ipv6Prefix(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32)),
+            (RadiusAttributeType::Ipv4Prefix, vec![0, 24, 192, 0, 2, 0], RadiusValue::Ipv4Prefix(Ipv4Addr::new(192, 0, 2, 0), 24)),
+            (RadiusAttributeType::Date, vec![0, 0, 0, 60], RadiusValue::Date(UNIX_EPOCH + Duration::from_secs(60))),
+            (RadiusAttributeType::Octets, vec![0xff], RadiusValue::Bytes(vec![0xff])),
+        ];
+
+        for (atype, bytes, value) in cases.iter() {
+            assert_eq!(&RadiusValue::decode(atype, bytes).unwrap(), value, "{:?}", atype);
+            assert_eq!(&value.encode(), bytes, "{:?}", atype);
+        }
+
+        assert!(RadiusValue::decode(&RadiusAttributeType::Integer, &[0]).is_err());
+        assert!(RadiusValue::decode(&RadiusAttributeType::String, &[0xff]).is_err());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(7u32.into_radius_value(&RadiusAttributeType::Byte).unwrap(), RadiusValue::U8(7));
+        assert_eq!(60u32.into_radius_value(&RadiusAttributeType::Date).unwrap(), RadiusValue::Date(UNIX_EPOCH + Duration::from_secs(60)));
+        assert_eq!((-1i32).into_radius_value(&RadiusAttributeType::Signed).unwrap(), RadiusValue::I32(-1));
+        assert_eq!("10.0.0.1".into_radius_value(&RadiusAttributeType::IpAddr).unwrap(), RadiusValue::Ipv4Addr(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!((Ipv6Addr::LOCALHOST, 64).into_radius_value(&RadiusAttributeType::Ipv6Prefix).unwrap(), RadiusValue::Ipv6Prefix(Ipv6Addr::LOCALHOST, 64));
+        assert_eq!((Ipv4Addr::new(10, 0, 0, 0), 8).into_radius_value(&RadiusAttributeType::Ipv4Prefix).unwrap(), RadiusValue::Ipv4Prefix(Ipv4Addr::new(10, 0, 0, 0), 8));
+        assert!((Ipv4Addr::new(10, 0, 0, 0), 33).into_radius_value(&RadiusAttributeType::Ipv4Prefix).is_err());
+        assert_eq!(RadiusValue::U32(1).into_radius_value(&RadiusAttributeType::Integer).unwrap(), RadiusValue::U32(1));
+
+        assert_eq!(256u32.into_radius_value(&RadiusAttributeType::Byte).err().unwrap(), "Value out of range for byte: 256");
+        assert_eq!((-1i32).into_radius_value(&RadiusAttributeType::Integer).err().unwrap(), "Value out of range for integer: -1");
+        assert_eq!(Ipv4Addr::LOCALHOST.into_radius_value(&RadiusAttributeType::Integer).err().unwrap(), "Cannot convert ipaddr into integer");
+        assert_eq!(RadiusValue::U32(1).into_radius_value(&RadiusAttributeType::Short).err().unwrap(), "Cannot convert integer into short");
+        assert_eq!(vec![0].into_radius_value(&RadiusAttributeType::Integer).err().unwrap(), "Invalid length for integer: 1 bytes, expected 4");
+        assert!("x".into_radius_value(&RadiusAttributeType::Integer).is_err());
+    }
+}
@@ -1,16 +1,39 @@
+use std::fmt;
 use std::str::FromStr;
 
 use super::atype::RadiusAttributeType;
+use super::flags::RadiusAttributeFlags;
+use super::super::RadiusVendorDef;
 
 /// RADIUS attribute definition
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct RadiusAttributeDef {
     name: String,
     code: u8,
     atype: RadiusAttributeType,
+    vendor: Option<RadiusVendorDef>,
+    flags: RadiusAttributeFlags,
+    parent: Option<Box<RadiusAttributeDef>>,
+    unknown: bool,
 }
 
 impl RadiusAttributeDef {
+    /// synthetic octets definition of an attribute missing from the dictionary,
+    /// named "Attr-" followed by its dotted identifier, e.g. "Attr-26.10415.1"
+    pub fn unknown(code: u8, vendor: Option<RadiusVendorDef>, parent: Option<RadiusAttributeDef>) -> RadiusAttributeDef {
+        let mut res = RadiusAttributeDef {
+            name: String::new(),
+            code,
+            atype: RadiusAttributeType::Octets,
+            vendor,
+            flags: RadiusAttributeFlags::default(),
+            parent: parent.map(Box::new),
+            unknown: true,
+        };
+        res.name = format!("Attr-{}", res.get_oid());
+        res
+    }
+
     /// retrieve attribute name
     pub fn get_name(&self) -> &str {
         &self.name
@@ -20,6 +43,65 @@ impl RadiusAttributeDef {
     pub fn get_code(&self) -> u8 {
         self.code
     }
+
+    /// retrieve attribute data type
+    pub fn get_type(&self) -> &RadiusAttributeType {
+        &self.atype
+    }
+
+    /// retrieve attribute flags
+    pub fn get_flags(&self) -> &RadiusAttributeFlags {
+        &self.flags
+    }
+
+    /// retrieve attribute vendor, if it is a Vendor-Specific one
+    pub fn get_vendor(&self) -> Option<&RadiusVendorDef> {
+        self.vendor.as_ref()
+    }
+
+    /// retrieve the enclosing attribute definition, e.g. Extended-Attribute-1 for Frag-Status (241.1)
+    pub fn get_parent(&self) -> Option<&RadiusAttributeDef> {
+        self.parent.as_deref()
+    }
+
+    /// true if attribute is a top level one, not defined inside a vendor block or an extended space
+    pub fn is_standard(&self) -> bool {
+        self.vendor.is_none() && self.parent.is_none()
+    }
+
+    /// true if the definition is a synthetic one, made for an attribute missing from the dictionary
+    pub fn is_unknown(&self) -> bool {
+        self.unknown
+    }
+
+    /// dotted numeric identifier, e.g. "1", "26.10415.1", "241.1" or "241.26.10415.1"
+    pub fn get_oid(&self) -> String {
+        match (self.get_parent(), self.get_vendor()) {
+            (Some(parent), Some(vendor)) if *parent.get_type() == RadiusAttributeType::Evs => format!("{}.{}.{}", parent.get_oid(), vendor.get_id(), self.code),
+            (Some(parent), _) => format!("{}.{}", parent.get_oid(), self.code),
+            (None, Some(vendor)) => format!("26.{}.{}", vendor.get_id(), self.code),
+            (None, None) => self.code.to_string(),
+        }
+    }
+
+    pub(crate) fn set_vendor(&mut self, vendor: RadiusVendorDef) {
+        self.vendor = Some(vendor);
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: RadiusAttributeDef) {
+        self.parent = Some(Box::new(parent));
+    }
+}
+
+impl fmt::Display for RadiusAttributeDef {
+    /// ATTRIBUTE row, the code being the full dotted identifier
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ATTRIBUTE\t{}\t{}\t{}", self.name, self.get_oid(), self.atype.get_name())?;
+        if !self.flags.is_empty() {
+            write!(f, "\t{}", self.flags)?;
+        }
+        Ok(())
+    }
 }
 
 impl FromStr for RadiusAttributeDef {
@@ -27,17 +109,33 @@ impl FromStr for RadiusAttributeDef {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split_whitespace().collect();
-        if parts.len() != 4 {
+        if parts.len() != 4 && parts.len() != 5 {
             return Err(format!("Malformed attribute row: {}", s));
         }
         if parts[0] != "ATTRIBUTE" {
             return Err(format!("Not an attribute row: {}", s));
         }
 
+        let atype = RadiusAttributeType::from_str(parts[3])?;
+        let flags = match parts.get(4) {
+            Some(f) => RadiusAttributeFlags::from_str(f)?,
+            None => RadiusAttributeFlags::default(),
+        };
+        if flags.is_array() && atype.get_size().is_none() {
+            return Err(format!("Array flag requires a fixed size type: {}", s));
+        }
+        if flags.is_concat() && atype != RadiusAttributeType::Octets {
+            return Err(format!("Concat flag requires octets type: {}", s));
+        }
+
         Ok(RadiusAttributeDef {
             name: parts[1].to_owned(),
             code: parts[2].parse::<u8>().map_err(|e| format!("Malformed attriute code: {:?}", e))?,
-            atype: RadiusAttributeType::from_str(parts[3])?,
+            atype,
+            vendor: None,
+            flags,
+            parent: None,
+            unknown: false,
         })
     }
 }
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// RADIUS attribute value encryption scheme (dictionary `encrypt=` flag)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RadiusEncryption {
+    /// 1 = User-Password hiding (RFC 2865)
+    UserPassword,
+    /// 2 = salted encryption, Tunnel-Password style (RFC 2868)
+    TunnelPassword,
+    /// 3 = Ascend secret
+    AscendSecret,
+}
+
+impl RadiusEncryption {
+    /// converts from dictionary representation
+    pub fn from_u8(i: u8) -> Result<RadiusEncryption, String> {
+        match i {
+            1u8 => Ok(RadiusEncryption::UserPassword),
+            2u8 => Ok(RadiusEncryption::TunnelPassword),
+            3u8 => Ok(RadiusEncryption::AscendSecret),
+            _ => Err(format!("Unknown RadiusEncryption {}", i)),
+        }
+    }
+
+    /// converts into dictionary representation
+    pub fn to_u8(self) -> u8 {
+        match self {
+            RadiusEncryption::UserPassword => 1u8,
+            RadiusEncryption::TunnelPassword => 2u8,
+            RadiusEncryption::AscendSecret => 3u8,
+        }
+    }
+}
+
+/// RADIUS attribute definition flags, the optional fifth column of an ATTRIBUTE row
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct RadiusAttributeFlags {
+    encrypt: Option<RadiusEncryption>,
+    has_tag: bool,
+    concat: bool,
+    array: bool,
+}
+
+impl RadiusAttributeFlags {
+    /// retrieve value encryption scheme
+    pub fn get_encrypt(&self) -> Option<RadiusEncryption> {
+        self.encrypt
+    }
+
+    /// true if the value is preceded by a tag (RFC 2868)
+    pub fn has_tag(&self) -> bool {
+        self.has_tag
+    }
+
+    /// true if long values are split over consecutive attributes
+    pub fn is_concat(&self) -> bool {
+        self.concat
+    }
+
+    /// true if the value packs multiple fixed size elements
+    pub fn is_array(&self) -> bool {
+        self.array
+    }
+
+    /// true if no flag is set
+    pub fn is_empty(&self) -> bool {
+        *self == RadiusAttributeFlags::default()
+    }
+}
+
+impl FromStr for RadiusAttributeFlags {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut res = RadiusAttributeFlags::default();
+        for flag in s.split(',') {
+            match flag {
+                "has_tag" => res.has_tag = true,
+                "concat" => res.concat = true,
+                "array" => res.array = true,
+                _ if flag.starts_with("encrypt=") => {
+                    let i = flag["encrypt=".len()..].parse::<u8>().map_err(|e| format!("Malformed attribute encryption: {:?}", e))?;
+                    res.encrypt = Some(RadiusEncryption::from_u8(i)?);
+                },
+                _ => return Err(format!("Unrecognized attribute flag: {}", flag)),
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl fmt::Display for RadiusAttributeFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut flags = Vec::new();
+        if self.has_tag {
+            flags.push("has_tag".to_owned());
+        }
+        if let Some(encrypt) = self.encrypt {
+            flags.push(format!("encrypt={}", encrypt.to_u8()));
+        }
+        if self.concat {
+            flags.push("concat".to_owned());
+        }
+        if self.array {
+            flags.push("array".to_owned());
+        }
+        write!(f, "{}", flags.join(","))
+    }
+}
@@ -1,14 +1,40 @@
 pub mod def;
 pub mod atype;
+pub mod flags;
+pub mod avalue;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::SystemTime;
 
 use self::def::RadiusAttributeDef;
+use self::atype::{RadiusAttributeType, MAX_VALUE_LENGTH};
+use self::avalue::RadiusValue;
+
+/// Vendor-Specific attribute code
+pub const VENDOR_SPECIFIC: u8 = 26;
+
+/// "M" (more) flag of long extended attributes (RFC 6929)
+pub const LONG_EXTENDED_MORE: u8 = 0x80;
+
+/// highest tag value of has_tag attributes (RFC 2868)
+pub const MAX_TAG: u8 = 0x1f;
 
 /// A simple association between a RADIUS attribute definition and byte array value
-/// TODO: return typed value based on definition
-#[derive(Clone)]
+/// tlv attributes may hold nested attributes, typed values are exposed by `get_typed_value` and friends
+#[derive(Clone, Debug)]
 pub struct RadiusAttribute {
     definition: RadiusAttributeDef,
     value: Vec<u8>,
+    tag: Option<u8>,
+    children: Vec<RadiusAttribute>,
+    // wire position of the attribute carrying the value when decoded, array values sharing it are packed back together
+    container: Option<usize>,
+    // data lengths of the fragments of a decoded long extended or concat value
+    fragments: Vec<usize>,
+    // value of an attribute flagged with encrypt= is hidden, as on the wire
+    hidden: bool,
 }
 
 impl RadiusAttribute {
@@ -17,30 +43,529 @@ impl RadiusAttribute {
         RadiusAttribute {
             definition,
             value,
+            tag: None,
+            children: Vec::new(),
+            container: None,
+            fragments: Vec::new(),
+            hidden: false,
         }
     }
 
+    /// decodes a wire value according to definition flags
+    /// tag is extracted and array values are split in one attribute per element,
+    /// values of attributes flagged with encrypt= are kept hidden
+    pub fn from_wire(definition: RadiusAttributeDef, value: &[u8]) -> Vec<RadiusAttribute> {
+        let mut res = Self::split_wire(definition, value);
+        for a in res.iter_mut() {
+            a.hidden = a.definition.get_flags().get_encrypt().is_some();
+        }
+        res
+    }
+
+    fn split_wire(definition: RadiusAttributeDef, value: &[u8]) -> Vec<RadiusAttribute> {
+        let mut value = value;
+        let mut tag = None;
+        if definition.get_flags().has_tag() && !value.is_empty() {
+            if *definition.get_type() == RadiusAttributeType::Integer {
+                // tag takes the place of the most significant byte
+                tag = Some(value[0]);
+                let mut res = Self::new(definition, vec![0]);
+                res.value.extend_from_slice(&value[1..]);
+                res.tag = tag;
+                return vec![res];
+            }
+            // tag is optional, unless value is encrypted
+            if value[0] <= MAX_TAG || definition.get_flags().get_encrypt().is_some() {
+                tag = Some(value[0]);
+                value = &value[1..];
+            }
+        }
+
+        let chunk = match definition.get_type().get_size() {
+            Some(size) if definition.get_flags().is_array() && value.len() % size == 0 => size,
+            _ => value.len(),
+        };
+        if chunk == 0 {
+            let mut res = Self::new(definition, Vec::new());
+            res.tag = tag;
+            return vec![res];
+        }
+
+        value.chunks(chunk).map(|v| {
+            let mut res = Self::new(definition.clone(), v.to_vec());
+            res.tag = tag;
+            res
+        }).collect()
+    }
+
     /// retrieve attribute definition
     pub fn get_definition(&self) -> &RadiusAttributeDef {
         &self.definition
     }
 
     /// retrieve attribute value
+    /// for tlv attributes this is the raw content, children are exposed by `get_children`
     pub fn get_value(&self) -> &[u8] {
         &self.value
     }
 
     /// set attribute value
+    /// values of attributes flagged with encrypt= are set in clear, to be hidden when the packet is encoded
     pub fn set_value(&mut self, data: Vec<u8>) {
         self.value = data;
+        self.hidden = false;
+    }
+
+    /// whether the value of an attribute flagged with encrypt= is hidden, as on the wire
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub(crate) fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub(crate) fn get_children_mut(&mut self) -> &mut [RadiusAttribute] {
+        &mut self.children
+    }
+
+    // values flagged with encrypt= never go on the wire in clear
+    fn check_hidden(&self) -> Result<(), String> {
+        if self.definition.get_flags().get_encrypt().is_some() && !self.hidden {
+            return Err(format!("Value of {} not hidden, the packet must be encoded with encode_packet", self.definition.get_name()));
+        }
+        self.children.iter().try_for_each(|c| c.check_hidden())
+    }
+
+    /// retrieve attribute value, typed after the definition data type
+    pub fn get_typed_value(&self) -> Result<RadiusValue, String> {
+        RadiusValue::decode(self.definition.get_type(), &self.value).map_err(|e| format!("{}: {}", self.definition.get_name(), e))
+    }
+
+    /// retrieve the value of a string attribute
+    pub fn get_string(&self) -> Result<String, String> {
+        match self.get_typed_value()? {
+            RadiusValue::String(s) => Ok(s),
+            v => Err(self.mismatch("string", &v)),
+        }
+    }
+
+    /// retrieve the value of a byte, short or integer attribute
+    pub fn get_u32(&self) -> Result<u32, String> {
+        match self.get_typed_value()? {
+            RadiusValue::U8(n) => Ok(u32::from(n)),
+            RadiusValue::U16(n) => Ok(u32::from(n)),
+            RadiusValue::U32(n) => Ok(n),
+            v => Err(self.mismatch("integer", &v)),
+        }
+    }
+
+    /// retrieve the value of an integer64 attribute
+    pub fn get_u64(&self) -> Result<u64, String> {
+        match self.get_typed_value()? {
+            RadiusValue::U64(n) => Ok(n),
+            v => Err(self.mismatch("integer64", &v)),
+        }
+    }
+
+    /// retrieve the value of a signed attribute
+    pub fn get_i32(&self) -> Result<i32, String> {
+        match self.get_typed_value()? {
+            RadiusValue::I32(n) => Ok(n),
+            v => Err(self.mismatch("signed", &v)),
+        }
+    }
+
+    /// retrieve the value of an ipaddr attribute
+    pub fn get_ipv4(&self) -> Result<Ipv4Addr, String> {
+        match self.get_typed_value()? {
+            RadiusValue::Ipv4Addr(ip) => Ok(ip),
+            v => Err(self.mismatch("ipaddr", &v)),
+        }
+    }
+
+    /// retrieve the value of an ipv6addr attribute
+    pub fn get_ipv6(&self) -> Result<Ipv6Addr, String> {
+        match self.get_typed_value()? {
+            RadiusValue::Ipv6Addr(ip) => Ok(ip),
+            v => Err(self.mismatch("ipv6addr", &v)),
+        }
+    }
+
+    /// retrieve the address and length of an ipv6prefix attribute
+    pub fn get_ipv6_prefix(&self) -> Result<(Ipv6Addr, u8), String> {
+        match self.get_typed_value()? {
+            RadiusValue::Ipv6Prefix(ip, len) => Ok((ip, len)),
+            v => Err(self.mismatch("ipv6prefix", &v)),
+        }
+    }
+
+    /// retrieve the address and length of an ipv4prefix attribute
+    pub fn get_ipv4_prefix(&self) -> Result<(Ipv4Addr, u8), String> {
+        match self.get_typed_value()? {
+            RadiusValue::Ipv4Prefix(ip, len) => Ok((ip, len)),
+            v => Err(self.mismatch("ipv4prefix", &v)),
+        }
+    }
+
+    /// retrieve the value of a date attribute
+    pub fn get_date(&self) -> Result<SystemTime, String> {
+        match self.get_typed_value()? {
+            RadiusValue::Date(time) => Ok(time),
+            v => Err(self.mismatch("date", &v)),
+        }
+    }
+
+    /// radclient style textual value: quoted strings, dotted addresses, decimal numbers, hexadecimal bytes
+    /// malformed values fall back to hexadecimal
+    pub fn get_text_value(&self) -> String {
+        let atype = self.definition.get_type();
+        match atype {
+            RadiusAttributeType::String => match std::str::from_utf8(&self.value) {
+                Ok(s) => {
+                    let mut res = String::from("\"");
+                    for c in s.chars() {
+                        match c {
+                            '"' => res.push_str("\\\""),
+                            '\\' => res.push_str("\\\\"),
+                            '\n' => res.push_str("\\n"),
+                            '\r' => res.push_str("\\r"),
+                            '\t' => res.push_str("\\t"),
+                            c => res.push(c),
+                        }
+                    }
+                    res.push('"');
+                    res
+                },
+                Err(_) => self.get_hex_value(),
+            },
+            _ => atype.decode(&self.value).unwrap_or_else(|_| self.get_hex_value()),
+        }
+    }
+
+    // "0x" prefixed hexadecimal value, whatever its length
+    fn get_hex_value(&self) -> String {
+        format!("0x{}", self.value.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+    }
+
+    /// attribute name as used in text listings, followed by the tag of has_tag attributes, e.g. "Tunnel-Type:1"
+    pub fn get_text_name(&self) -> String {
+        match self.tag {
+            Some(tag) if self.definition.get_flags().has_tag() => format!("{}:{}", self.definition.get_name(), tag),
+            _ => self.definition.get_name().to_owned(),
+        }
+    }
+
+    fn mismatch(&self, expected: &str, value: &RadiusValue) -> String {
+        format!("{}: expected {} value, attribute type is {}", self.definition.get_name(), expected, value.get_type_name())
+    }
+
+    /// retrieve attribute tag, meaningful for has_tag attributes only
+    pub fn get_tag(&self) -> Option<u8> {
+        self.tag
+    }
+
+    /// set attribute tag, meaningful for has_tag attributes only
+    pub fn set_tag(&mut self, tag: Option<u8>) {
+        self.tag = tag;
+    }
+
+    /// tags a has_tag attribute, e.g. to group the Tunnel-* attributes describing the same tunnel
+    /// tags range from 0 to 0x1f
+    pub fn with_tag(mut self, tag: u8) -> Result<RadiusAttribute, String> {
+        if !self.definition.get_flags().has_tag() {
+            return Err(format!("Attribute {} has no tag", self.definition.get_name()));
+        }
+        if tag > MAX_TAG {
+            return Err(format!("Invalid tag for {}: {}", self.definition.get_name(), tag));
+        }
+        self.tag = Some(tag);
+        Ok(self)
+    }
+
+    /// retrieve nested attributes of a tlv attribute
+    pub fn get_children(&self) -> &[RadiusAttribute] {
+        &self.children
+    }
+
+    /// retrieves a nested attribute by name
+    pub fn get_child_by_name(&self, name: &str) -> Option<&RadiusAttribute> {
+        self.children.iter().find(|c| c.definition.get_name() == name)
+    }
+
+    /// retrieves a mutable reference to a nested attribute by name
+    pub fn get_child_by_name_mut(&mut self, name: &str) -> Option<&mut RadiusAttribute> {
+        self.children.iter_mut().find(|c| c.definition.get_name() == name)
+    }
+
+    /// adds a nested attribute to a tlv attribute
+    /// children, when present, take the place of the raw value on the wire
+    pub fn add_child(&mut self, child: RadiusAttribute) -> Result<(), String> {
+        if *self.definition.get_type() != RadiusAttributeType::Tlv || child.definition.get_parent().map(|p| p.get_name()) != Some(self.definition.get_name()) {
+            return Err(format!("Attribute {} cannot contain {}", self.definition.get_name(), child.definition.get_name()));
+        }
+
+        self.children.push(child);
+        match self.get_wire_value() {
+            Ok(value) => {
+                self.value = value;
+                Ok(())
+            },
+            Err(e) => {
+                self.children.pop();
+                Err(e)
+            },
+        }
+    }
+
+    // records where a decoded attribute comes from, to encode it back the same way
+    pub(crate) fn set_container(&mut self, container: usize) {
+        self.container = Some(container);
+    }
+
+    pub(crate) fn set_fragments(&mut self, fragments: Vec<usize>) {
+        self.fragments = fragments;
+    }
+
+    // reassembles the next fragment of a concat value, as received
+    pub(crate) fn append_fragment(&mut self, value: &[u8]) {
+        if self.fragments.is_empty() {
+            self.fragments.push(self.value.len());
+        }
+        self.value.extend_from_slice(value);
+        self.fragments.push(value.len());
+    }
+
+    // whether both attributes were decoded from the same Vendor-Specific attribute
+    pub(crate) fn shares_vsa(&self, other: &RadiusAttribute) -> bool {
+        let vendor = |a: &RadiusAttribute| match (a.definition.get_parent(), a.definition.get_vendor()) {
+            (None, Some(vendor)) => Some(vendor.get_id()),
+            _ => None,
+        };
+        self.container.is_some() && self.container == other.container && vendor(self).is_some() && vendor(self) == vendor(other)
+    }
+
+    // encodes vendor attributes decoded from a single Vendor-Specific attribute back into one,
+    // falling back to a Vendor-Specific attribute each when they don't fit together anymore
+    pub(crate) fn encode_vsa(group: &[RadiusAttribute]) -> Result<Vec<u8>, String> {
+        let mut vsa = Vec::new();
+        if group.len() > 1 {
+            for a in group.iter() {
+                a.check_hidden()?;
+                a.check_length()?;
+                if let Some(vendor) = a.definition.get_vendor() {
+                    vsa.append(&mut vendor.encode(a.definition.get_code(), &a.get_wire_value()?)?);
+                }
+            }
+        }
+        match (group[0].definition.get_vendor(), u8::try_from(6 + vsa.len())) {
+            (Some(vendor), Ok(len)) if group.len() > 1 => {
+                let mut res = vec![VENDOR_SPECIFIC, len];
+                res.extend_from_slice(&vendor.get_id().to_be_bytes());
+                res.append(&mut vsa);
+                Ok(res)
+            },
+            _ => {
+                let mut res = Vec::new();
+                for a in group.iter() {
+                    res.append(&mut a.get_bytes()?);
+                }
+                Ok(res)
+            },
+        }
+    }
+
+    // packs consecutive values of an array attribute in a single attribute
+    // values decoded from different attributes are kept apart, others are packed as long as they fit
+    // returns the packed attribute and the number of attributes it covers
+    pub(crate) fn pack(attributes: &[RadiusAttribute]) -> (RadiusAttribute, usize) {
+        let mut res = attributes[0].clone();
+        if !res.definition.get_flags().is_array() {
+            return (res, 1);
+        }
+
+        let mut count = 1;
+        for a in attributes[1..].iter() {
+            if a.definition.get_name() != res.definition.get_name() || a.tag != res.tag || a.container != res.container
+                || res.value.len() + a.value.len() > res.get_max_length() {
+                break;
+            }
+            res.value.extend_from_slice(&a.value);
+            count += 1;
+        }
+
+        (res, count)
+    }
+
+    // attribute value as it goes on the wire, tag included
+    fn get_wire_value(&self) -> Result<Vec<u8>, String> {
+        if !self.children.is_empty() {
+            let mut res = Vec::new();
+            let mut i = 0;
+            while i < self.children.len() {
+                let (c, packed) = Self::pack(&self.children[i..]);
+                let value = c.get_wire_value()?;
+                res.push(c.definition.get_code());
+                res.push(Self::length(&c.definition, 2 + value.len())?);
+                res.extend_from_slice(&value);
+                i += packed;
+            }
+            return Ok(res);
+        }
+
+        Ok(match self.tag {
+            Some(tag) if self.definition.get_flags().has_tag() => {
+                let mut res = vec![tag];
+                if *self.definition.get_type() == RadiusAttributeType::Integer && !self.value.is_empty() {
+                    res.extend_from_slice(&self.value[1..]);
+                }
+                else {
+                    res.extend_from_slice(&self.value);
+                }
+                res
+            },
+            // untagged values that would be read as tagged get a 0 tag, encrypted ones always carry one
+            None if self.definition.get_flags().has_tag() && *self.definition.get_type() != RadiusAttributeType::Integer
+                && (self.definition.get_flags().get_encrypt().is_some() || self.value.first().map_or(false, |b| *b <= MAX_TAG)) => {
+                let mut res = vec![0];
+                res.extend_from_slice(&self.value);
+                res
+            },
+            _ => self.value.clone(),
+        })
     }
 
     /// RADIUS attribute byte array representation
-    pub fn get_bytes(&self) -> Vec<u8> {
+    /// concat attributes longer than a single attribute are split over consecutive attributes,
+    /// long extended ones are fragmented using the "M" (more) flag, decoded ones of both as they were received
+    /// fails when the value doesn't fit, see `check_length`, or when a value flagged with encrypt= is not hidden
+    pub fn get_bytes(&self) -> Result<Vec<u8>, String> {
+        self.check_hidden()?;
+        self.check_length()?;
+        Self::encode(&self.definition, &self.get_wire_value()?, self.definition.get_flags().is_concat(), &self.fragments)
+    }
+
+    /// checks that the value fits the attribute on the wire
+    /// only concat attributes and long extended ones may exceed a single attribute, being split on encode,
+    /// long extended values are still bound by their data type
+    pub fn check_length(&self) -> Result<(), String> {
+        let len = self.get_wire_value()?.len();
+        let max = match self.definition.get_type().get_max_size() {
+            Some(size) if self.get_max_length() > MAX_VALUE_LENGTH => size,
+            _ => self.get_max_length(),
+        };
+        if len > max && !self.definition.get_flags().is_concat() {
+            return Err(format!("Value too long for {}: {} bytes, maximum {}", self.definition.get_name(), len, max));
+        }
+        Ok(())
+    }
+
+    /// maximum value length fitting a single attribute
+    pub fn get_max_length(&self) -> usize {
+        Self::max_length(&self.definition)
+    }
+
+    fn max_length(def: &RadiusAttributeDef) -> usize {
+        match (def.get_parent(), def.get_vendor()) {
+            (Some(parent), Some(_)) if *parent.get_type() == RadiusAttributeType::Evs => Self::max_length(parent).saturating_sub(5),
+            (Some(parent), _) if *parent.get_type() == RadiusAttributeType::Tlv => Self::max_length(parent).saturating_sub(2),
+            (Some(parent), _) if *parent.get_type() == RadiusAttributeType::LongExtended => usize::MAX,
+            (Some(_), _) => MAX_VALUE_LENGTH - 1,
+            (None, Some(vendor)) => MAX_VALUE_LENGTH - 4 - vendor.get_type_size() - vendor.get_length_size() - if vendor.has_continuation() { 1 } else { 0 },
+            (None, None) => MAX_VALUE_LENGTH,
+        }
+    }
+
+    // length byte of an attribute header, failing instead of truncating
+    fn length(def: &RadiusAttributeDef, len: usize) -> Result<u8, String> {
+        u8::try_from(len).map_err(|_| format!("Value too long for {}: {} bytes encoded, maximum {}", def.get_name(), len, u8::MAX))
+    }
+
+    // splits a value in fragments of at most `max` bytes, after `fragments` when they still match the value
+    fn fragment<'a>(value: &'a [u8], fragments: &[usize], max: usize) -> Vec<&'a [u8]> {
+        if value.is_empty() {
+            vec![value]
+        }
+        else if !fragments.is_empty() && fragments.iter().sum::<usize>() == value.len() && fragments.iter().all(|f| *f <= max) {
+            let mut rest = value;
+            fragments.iter().map(|f| {
+                let (fragment, next) = rest.split_at(*f);
+                rest = next;
+                fragment
+            }).collect()
+        }
+        else {
+            value.chunks(max).collect()
+        }
+    }
+
+    // wraps a value in the wire attribute(s) of a definition
+    // concat and long extended values are fragmented after `fragments` when they still match the value
+    fn encode(def: &RadiusAttributeDef, value: &[u8], split: bool, fragments: &[usize]) -> Result<Vec<u8>, String> {
+        if split {
+            let chunks = Self::fragment(value, fragments, Self::max_length(def));
+            if chunks.len() > 1 {
+                let mut res = Vec::new();
+                for c in chunks {
+                    res.append(&mut Self::encode(def, c, false, &[])?);
+                }
+                return Ok(res);
+            }
+        }
+
         let mut res = Vec::new();
-        res.push(self.definition.get_code());
-        res.push((2 + self.value.len()) as u8);
-        res.append(&mut self.value.clone());
-        res
+        match (def.get_parent(), def.get_vendor()) {
+            (Some(parent), Some(vendor)) if *parent.get_type() == RadiusAttributeType::Evs => {
+                let mut evs = vendor.get_id().to_be_bytes().to_vec();
+                evs.push(def.get_code());
+                evs.extend_from_slice(value);
+                res = Self::encode(parent, &evs, false, fragments)?;
+            },
+            (Some(parent), _) if *parent.get_type() == RadiusAttributeType::Tlv => {
+                // a standalone nested attribute is wrapped by its ancestors
+                let mut tlv = vec![def.get_code(), Self::length(def, 2 + value.len())?];
+                tlv.extend_from_slice(value);
+                res = Self::encode(parent, &tlv, false, fragments)?;
+            },
+            (Some(parent), _) if *parent.get_type() == RadiusAttributeType::LongExtended => {
+                let fragments = Self::fragment(value, fragments, MAX_VALUE_LENGTH - 2);
+                for (n, f) in fragments.iter().enumerate() {
+                    res.push(parent.get_code());
+                    res.push(Self::length(def, 4 + f.len())?);
+                    res.push(def.get_code());
+                    res.push(if n + 1 < fragments.len() { LONG_EXTENDED_MORE } else { 0 });
+                    res.extend_from_slice(f);
+                }
+            },
+            (Some(parent), _) => {
+                res.push(parent.get_code());
+                res.push(Self::length(def, 3 + value.len())?);
+                res.push(def.get_code());
+                res.extend_from_slice(value);
+            },
+            (None, Some(vendor)) => {
+                let mut vsa = vendor.encode(def.get_code(), value)?;
+                res.push(VENDOR_SPECIFIC);
+                res.push(Self::length(def, 6 + vsa.len())?);
+                res.extend_from_slice(&vendor.get_id().to_be_bytes());
+                res.append(&mut vsa);
+            },
+            (None, None) => {
+                res.push(def.get_code());
+                res.push(Self::length(def, 2 + value.len())?);
+                res.extend_from_slice(value);
+            },
+        }
+        Ok(res)
+    }
+}
+
+impl fmt::Display for RadiusAttribute {
+    /// radclient style `Name = value` text, tlv children are listed instead of their parent
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.children.is_empty() {
+            return write!(f, "{}", self.children.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(", "));
+        }
+        write!(f, "{} = {}", self.get_text_name(), self.get_text_value())
     }
 }
@@ -1,5 +1,8 @@
-/// RADIUS message types
-#[derive(Clone, PartialEq)]
+use std::fmt;
+use std::str::FromStr;
+
+/// RADIUS message types (IANA RADIUS Packet Type Codes registry)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum RadiusCode {
     /// 1 = Access-Request
     AccessRequest,
@@ -11,45 +14,198 @@ pub enum RadiusCode {
     AccountingRequest,
     /// 5 = Accounting-Response
     AccountingResponse,
+    /// 6 = Accounting-Status, now Interim-Accounting
+    AccountingStatus,
+    /// 7 = Password-Request
+    PasswordRequest,
+    /// 8 = Password-Ack
+    PasswordAck,
+    /// 9 = Password-Reject
+    PasswordReject,
+    /// 10 = Accounting-Message
+    AccountingMessage,
     /// 11 = Access-Challenge
     AccessChallenge,
     /// 12 = Status-Server (experimental)
     StatusServer,
     /// 13 = Status-Client (experimental)
     StatusClient,
+    /// 21 = Resource-Free-Request
+    ResourceFreeRequest,
+    /// 22 = Resource-Free-Response
+    ResourceFreeResponse,
+    /// 23 = Resource-Query-Request
+    ResourceQueryRequest,
+    /// 24 = Resource-Query-Response
+    ResourceQueryResponse,
+    /// 25 = Alternate-Resource-Reclaim-Request
+    AlternateResourceReclaimRequest,
+    /// 26 = NAS-Reboot-Request
+    NasRebootRequest,
+    /// 27 = NAS-Reboot-Response
+    NasRebootResponse,
+    /// 29 = Next-Passcode
+    NextPasscode,
+    /// 30 = New-Pin
+    NewPin,
+    /// 31 = Terminate-Session
+    TerminateSession,
+    /// 32 = Password-Expired
+    PasswordExpired,
+    /// 33 = Event-Request
+    EventRequest,
+    /// 34 = Event-Response
+    EventResponse,
+    /// 40 = Disconnect-Request (RFC 5176)
+    DisconnectRequest,
+    /// 41 = Disconnect-ACK (RFC 5176)
+    DisconnectAck,
+    /// 42 = Disconnect-NAK (RFC 5176)
+    DisconnectNak,
+    /// 43 = CoA-Request (RFC 5176)
+    CoaRequest,
+    /// 44 = CoA-ACK (RFC 5176)
+    CoaAck,
+    /// 45 = CoA-NAK (RFC 5176)
+    CoaNak,
+    /// 50 = IP-Address-Allocate
+    IpAddressAllocate,
+    /// 51 = IP-Address-Release
+    IpAddressRelease,
+    /// 52 = Protocol-Error (RFC 7930)
+    ProtocolError,
     /// 255 = reserved
     Reserved,
+    /// any other code, experimental (250-253) and unassigned ones included
+    Unknown(UnknownCode),
 }
 
+/// number of an Unknown RadiusCode, never one of the codes having their own variant
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnknownCode(u8);
+
+impl UnknownCode {
+    /// code number
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+// known codes with their registry names
+const CODES: [(RadiusCode, u8, &str); 36] = [
+    (RadiusCode::AccessRequest, 1, "Access-Request"),
+    (RadiusCode::AccessAccept, 2, "Access-Accept"),
+    (RadiusCode::AccessReject, 3, "Access-Reject"),
+    (RadiusCode::AccountingRequest, 4, "Accounting-Request"),
+    (RadiusCode::AccountingResponse, 5, "Accounting-Response"),
+    (RadiusCode::AccountingStatus, 6, "Accounting-Status"),
+    (RadiusCode::PasswordRequest, 7, "Password-Request"),
+    (RadiusCode::PasswordAck, 8, "Password-Ack"),
+    (RadiusCode::PasswordReject, 9, "Password-Reject"),
+    (RadiusCode::AccountingMessage, 10, "Accounting-Message"),
+    (RadiusCode::AccessChallenge, 11, "Access-Challenge"),
+    (RadiusCode::StatusServer, 12, "Status-Server"),
+    (RadiusCode::StatusClient, 13, "Status-Client"),
+    (RadiusCode::ResourceFreeRequest, 21, "Resource-Free-Request"),
+    (RadiusCode::ResourceFreeResponse, 22, "Resource-Free-Response"),
+    (RadiusCode::ResourceQueryRequest, 23, "Resource-Query-Request"),
+    (RadiusCode::ResourceQueryResponse, 24, "Resource-Query-Response"),
+    (RadiusCode::AlternateResourceReclaimRequest, 25, "Alternate-Resource-Reclaim-Request"),
+    (RadiusCode::NasRebootRequest, 26, "NAS-Reboot-Request"),
+    (RadiusCode::NasRebootResponse, 27, "NAS-Reboot-Response"),
+    (RadiusCode::NextPasscode, 29, "Next-Passcode"),
+    (RadiusCode::NewPin, 30, "New-Pin"),
+    (RadiusCode::TerminateSession, 31, "Terminate-Session"),
+    (RadiusCode::PasswordExpired, 32, "Password-Expired"),
+    (RadiusCode::EventRequest, 33, "Event-Request"),
+    (RadiusCode::EventResponse, 34, "Event-Response"),
+    (RadiusCode::DisconnectRequest, 40, "Disconnect-Request"),
+    (RadiusCode::DisconnectAck, 41, "Disconnect-ACK"),
+    (RadiusCode::DisconnectNak, 42, "Disconnect-NAK"),
+    (RadiusCode::CoaRequest, 43, "CoA-Request"),
+    (RadiusCode::CoaAck, 44, "CoA-ACK"),
+    (RadiusCode::CoaNak, 45, "CoA-NAK"),
+    (RadiusCode::IpAddressAllocate, 50, "IP-Address-Allocate"),
+    (RadiusCode::IpAddressRelease, 51, "IP-Address-Release"),
+    (RadiusCode::ProtocolError, 52, "Protocol-Error"),
+    (RadiusCode::Reserved, 255, "Reserved"),
+];
+
 impl RadiusCode {
     /// converts from byte representation
+    #[deprecated(note = "never fails anymore, unassigned codes being kept as Unknown: use RadiusCode::from")]
     pub fn from_u8(i: u8) -> Result<RadiusCode, String> {
-        match i {
-            1u8 => Ok(RadiusCode::AccessRequest),
-            2u8 => Ok(RadiusCode::AccessAccept),
-            3u8 => Ok(RadiusCode::AccessReject),
-            4u8 => Ok(RadiusCode::AccountingRequest),
-            5u8 => Ok(RadiusCode::AccountingResponse),
-            11u8 => Ok(RadiusCode::AccessChallenge),
-            12u8 => Ok(RadiusCode::StatusServer),
-            13u8 => Ok(RadiusCode::StatusClient),
-            255u8 => Ok(RadiusCode::Reserved),
-            _ => Err(format!("Unknown RadiusCode {}", i)),
-        }
+        Ok(RadiusCode::from(i))
     }
 
     /// converts into byte representation
     pub fn to_u8(&self) -> u8 {
         match self {
-            RadiusCode::AccessRequest => 1u8,
-            RadiusCode::AccessAccept => 2u8,
-            RadiusCode::AccessReject => 3u8,
-            RadiusCode::AccountingRequest => 4u8,
-            RadiusCode::AccountingResponse => 5u8,
-            RadiusCode::AccessChallenge => 11u8,
-            RadiusCode::StatusServer => 12u8,
-            RadiusCode::StatusClient => 13u8,
-            RadiusCode::Reserved => 255u8,
+            RadiusCode::Unknown(i) => i.get(),
+            _ => CODES.iter().find(|(code, _, _)| code == self).map(|(_, n, _)| *n).unwrap_or_default(),
+        }
+    }
+
+    /// registry name, e.g. "CoA-Request", None for Unknown codes
+    pub fn get_name(&self) -> Option<&'static str> {
+        CODES.iter().find(|(code, _, _)| code == self).map(|(_, _, name)| *name)
+    }
+}
+
+impl From<u8> for RadiusCode {
+    /// converts from byte representation, unassigned codes being kept as Unknown
+    fn from(i: u8) -> RadiusCode {
+        CODES.iter().find(|(_, n, _)| *n == i).map(|(code, _, _)| *code).unwrap_or(RadiusCode::Unknown(UnknownCode(i)))
+    }
+}
+
+impl fmt::Display for RadiusCode {
+    /// registry name, or the number of Unknown codes
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.get_name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{}", self.to_u8()),
+        }
+    }
+}
+
+impl FromStr for RadiusCode {
+    type Err = String;
+
+    /// registry name, case insensitive, or code number
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(i) = s.parse::<u8>() {
+            return Ok(RadiusCode::from(i));
+        }
+        CODES.iter().find(|(_, _, name)| name.eq_ignore_ascii_case(s)).map(|(code, _, _)| *code).ok_or_else(|| format!("Unknown RadiusCode {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RadiusCode, CODES};
+    use std::str::FromStr;
+
+    #[test]
+    fn codes() {
+        for i in 0..=255u8 {
+            let code = RadiusCode::from(i);
+            assert_eq!(code.to_u8(), i);
+            assert_eq!(RadiusCode::from_str(&code.to_string()), Ok(code));
+            // assigned codes are never Unknown, so each byte has a single representation
+            assert_eq!(matches!(code, RadiusCode::Unknown(_)), code.get_name().is_none());
+        }
+        assert_eq!(CODES.iter().filter(|(code, n, _)| RadiusCode::from(*n) == *code).count(), CODES.len());
+
+        assert_eq!(RadiusCode::from(43), RadiusCode::CoaRequest);
+        assert!(matches!(RadiusCode::from(250), RadiusCode::Unknown(c) if c.get() == 250));
+        assert_eq!(RadiusCode::from(250).to_string(), "250");
+        #[allow(deprecated)]
+        {
+            assert_eq!(RadiusCode::from_u8(1), Ok(RadiusCode::AccessRequest));
         }
+        assert_eq!(RadiusCode::DisconnectAck.to_string(), "Disconnect-ACK");
+        assert_eq!(RadiusCode::from_str("protocol-error"), Ok(RadiusCode::ProtocolError));
+        assert!(RadiusCode::from_str("CoA").is_err());
     }
 }
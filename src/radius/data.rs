@@ -1,9 +1,18 @@
-use super::{RadiusCode, RadiusAttribute};
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::SystemTime;
+
+use super::{RadiusCode, RadiusAttribute, RadiusAttributeType, RadiusValue};
 
 use rand::{thread_rng, Rng};
 
+/// maximum length of a RADIUS packet (RFC 2865)
+pub const MAX_PACKET_LENGTH: usize = 4096;
+
 /// RADIUS packet
-#[derive(Clone)]
+/// attributes keep their insertion (or wire) order, repeated ones such as Proxy-State,
+/// Class or EAP-Message are encoded back in the same order
+#[derive(Clone, Debug)]
 pub struct RadiusData {
     code: RadiusCode,
     identifier: u8,
@@ -42,6 +51,16 @@ impl RadiusData {
         self.attributes.push(attr);
     }
 
+    /// retrieves all RADIUS attributes
+    pub fn get_attributes(&self) -> &[RadiusAttribute] {
+        &self.attributes
+    }
+
+    /// retrieves mutable references to all RADIUS attributes
+    pub fn get_attributes_mut(&mut self) -> &mut [RadiusAttribute] {
+        &mut self.attributes
+    }
+
     /// retrieves a RADIUS attribute by name
     pub fn get_attribute_by_name(&self, name: &str) -> Option<&RadiusAttribute> {
         for i in 0..self.attributes.len() {
@@ -62,20 +81,236 @@ impl RadiusData {
         None
     }
 
-    /// retrieves a RADIUS attribute by code
+    /// retrieves all the instances of a RADIUS attribute by name, in order
+    pub fn get_attributes_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a RadiusAttribute> + 'a {
+        self.attributes.iter().filter(move |a| a.get_definition().get_name() == name)
+    }
+
+    /// retrieves mutable references to all the instances of a RADIUS attribute by name, in order
+    pub fn get_attributes_by_name_mut<'a>(&'a mut self, name: &'a str) -> impl Iterator<Item = &'a mut RadiusAttribute> + 'a {
+        self.attributes.iter_mut().filter(move |a| a.get_definition().get_name() == name)
+    }
+
+    /// retrieves all the instances of a RADIUS attribute by code, in order, Vendor-Specific and extended attributes excluded
+    pub fn get_attributes_by_code(&self, code: u8) -> impl Iterator<Item = &RadiusAttribute> {
+        self.attributes.iter().filter(move |a| a.get_definition().is_standard() && a.get_definition().get_code() == code)
+    }
+
+    /// inserts a RADIUS attribute at a position, shifting the following ones
+    pub fn insert_attribute(&mut self, index: usize, attr: RadiusAttribute) -> Result<(), String> {
+        if index > self.attributes.len() {
+            return Err(format!("Attribute position out of range: {}, {} attributes", index, self.attributes.len()));
+        }
+        self.attributes.insert(index, attr);
+        Ok(())
+    }
+
+    /// removes the RADIUS attribute at a position
+    pub fn remove_attribute(&mut self, index: usize) -> Option<RadiusAttribute> {
+        if index < self.attributes.len() {
+            Some(self.attributes.remove(index))
+        }
+        else {
+            None
+        }
+    }
+
+    /// removes all the instances of a RADIUS attribute by name, returning them in order
+    pub fn remove_attributes_by_name(&mut self, name: &str) -> Vec<RadiusAttribute> {
+        let (removed, kept) = self.attributes.drain(..).partition(|a| a.get_definition().get_name() == name);
+        self.attributes = kept;
+        removed
+    }
+
+    /// replaces all the instances of a RADIUS attribute with a single one, at the position of the first instance
+    /// the attribute is appended when missing, replaced instances are returned in order
+    pub fn replace_attribute(&mut self, attr: RadiusAttribute) -> Vec<RadiusAttribute> {
+        let name = attr.get_definition().get_name().to_owned();
+        let index = self.attributes.iter().position(|a| a.get_definition().get_name() == name).unwrap_or(self.attributes.len());
+        let removed = self.remove_attributes_by_name(&name);
+        self.attributes.insert(index, attr);
+        removed
+    }
+
+    /// keeps only the RADIUS attributes matching a predicate, preserving their order
+    pub fn retain_attributes<F: FnMut(&RadiusAttribute) -> bool>(&mut self, f: F) {
+        self.attributes.retain(f);
+    }
+
+    /// retrieves a RADIUS attribute by dotted identifier, e.g. "241.1" or "26.10415.1", nested ones included
+    pub fn get_attribute_by_oid(&self, oid: &str) -> Option<&RadiusAttribute> {
+        let mut stack: Vec<&RadiusAttribute> = self.attributes.iter().rev().collect();
+        while let Some(a) = stack.pop() {
+            if a.get_definition().get_oid() == oid {
+                return Some(a);
+            }
+            stack.extend(a.get_children().iter().rev());
+        }
+        None
+    }
+
+    /// retrieves a mutable reference to a RADIUS attribute by dotted identifier, nested ones included
+    pub fn get_attribute_by_oid_mut(&mut self, oid: &str) -> Option<&mut RadiusAttribute> {
+        Self::find_oid_mut(&mut self.attributes, oid)
+    }
+
+    fn find_oid_mut<'a>(attributes: &'a mut [RadiusAttribute], oid: &str) -> Option<&'a mut RadiusAttribute> {
+        for a in attributes.iter_mut() {
+            if a.get_definition().get_oid() == oid {
+                return Some(a);
+            }
+            if let Some(res) = Self::find_oid_mut(a.get_children_mut(), oid) {
+                return Some(res);
+            }
+        }
+        None
+    }
+
+    /// retrieves a nested RADIUS attribute by dotted names path, e.g. "WiMAX-Capability.WiMAX-Release"
+    pub fn get_attribute_by_path(&self, path: &str) -> Option<&RadiusAttribute> {
+        let mut names = path.split('.');
+        let mut res = self.get_attribute_by_name(names.next()?)?;
+        for name in names {
+            res = res.get_child_by_name(name)?;
+        }
+        Some(res)
+    }
+
+    /// retrieves a mutable reference to a nested RADIUS attribute by dotted names path
+    pub fn get_attribute_by_path_mut(&mut self, path: &str) -> Option<&mut RadiusAttribute> {
+        let mut names = path.split('.');
+        let mut res = self.get_attribute_by_name_mut(names.next()?)?;
+        for name in names {
+            res = res.get_child_by_name_mut(name)?;
+        }
+        Some(res)
+    }
+
+    /// retrieves a tagged RADIUS attribute by name and tag, e.g. the Tunnel-Type of tunnel 1
+    pub fn get_attribute_by_name_and_tag(&self, name: &str, tag: u8) -> Option<&RadiusAttribute> {
+        self.attributes.iter().find(|a| a.get_definition().get_name() == name && a.get_tag() == Some(tag))
+    }
+
+    /// retrieves all the RADIUS attributes sharing a tag
+    pub fn get_attributes_by_tag(&self, tag: u8) -> Vec<&RadiusAttribute> {
+        self.attributes.iter().filter(|a| a.get_definition().get_flags().has_tag() && a.get_tag() == Some(tag)).collect()
+    }
+
+    /// groups tagged RADIUS attributes by tag, in order of first appearance
+    /// e.g. one group per tunnel for the Tunnel-* attributes
+    pub fn get_tag_groups(&self) -> Vec<(u8, Vec<&RadiusAttribute>)> {
+        let mut res: Vec<(u8, Vec<&RadiusAttribute>)> = Vec::new();
+        for a in self.attributes.iter().filter(|a| a.get_definition().get_flags().has_tag()) {
+            let tag = match a.get_tag() {
+                Some(tag) => tag,
+                None => continue,
+            };
+            match res.iter_mut().find(|(t, _)| *t == tag) {
+                Some((_, group)) => group.push(a),
+                None => res.push((tag, vec![a])),
+            }
+        }
+        res
+    }
+
+    /// retrieves the typed value of a RADIUS attribute by name
+    pub fn get_typed_value(&self, name: &str) -> Result<RadiusValue, String> {
+        self.get_typed(name, RadiusAttribute::get_typed_value)
+    }
+
+    /// retrieves the value of a string RADIUS attribute by name, e.g. "User-Name"
+    pub fn get_string(&self, name: &str) -> Result<String, String> {
+        self.get_typed(name, RadiusAttribute::get_string)
+    }
+
+    /// retrieves the value of a byte, short or integer RADIUS attribute by name, e.g. "Session-Timeout"
+    pub fn get_u32(&self, name: &str) -> Result<u32, String> {
+        self.get_typed(name, RadiusAttribute::get_u32)
+    }
+
+    /// retrieves the value of an integer64 RADIUS attribute by name
+    pub fn get_u64(&self, name: &str) -> Result<u64, String> {
+        self.get_typed(name, RadiusAttribute::get_u64)
+    }
+
+    /// retrieves the value of a signed RADIUS attribute by name
+    pub fn get_i32(&self, name: &str) -> Result<i32, String> {
+        self.get_typed(name, RadiusAttribute::get_i32)
+    }
+
+    /// retrieves the value of an ipaddr RADIUS attribute by name, e.g. "Framed-IP-Address"
+    pub fn get_ipv4(&self, name: &str) -> Result<Ipv4Addr, String> {
+        self.get_typed(name, RadiusAttribute::get_ipv4)
+    }
+
+    /// retrieves the value of an ipv6addr RADIUS attribute by name
+    pub fn get_ipv6(&self, name: &str) -> Result<Ipv6Addr, String> {
+        self.get_typed(name, RadiusAttribute::get_ipv6)
+    }
+
+    /// retrieves the address and length of an ipv6prefix RADIUS attribute by name
+    pub fn get_ipv6_prefix(&self, name: &str) -> Result<(Ipv6Addr, u8), String> {
+        self.get_typed(name, RadiusAttribute::get_ipv6_prefix)
+    }
+
+    /// retrieves the address and length of an ipv4prefix RADIUS attribute by name, e.g. "PMIP6-Home-IPv4-HoA"
+    pub fn get_ipv4_prefix(&self, name: &str) -> Result<(Ipv4Addr, u8), String> {
+        self.get_typed(name, RadiusAttribute::get_ipv4_prefix)
+    }
+
+    /// retrieves the value of a date RADIUS attribute by name, e.g. "Event-Timestamp"
+    pub fn get_date(&self, name: &str) -> Result<SystemTime, String> {
+        self.get_typed(name, RadiusAttribute::get_date)
+    }
+
+    fn get_typed<T>(&self, name: &str, get: fn(&RadiusAttribute) -> Result<T, String>) -> Result<T, String> {
+        self.get_attribute_by_name(name).ok_or_else(|| format!("Attribute not found: {}", name)).and_then(get)
+    }
+
+    /// adds a RADIUS attribute defined inside a tlv, creating its ancestors when missing
+    pub fn add_nested_attribute(&mut self, attr: RadiusAttribute) -> Result<(), String> {
+        let mut ancestors = Vec::new();
+        let mut def = attr.get_definition().get_parent();
+        while let Some(parent) = def {
+            if *parent.get_type() != RadiusAttributeType::Tlv {
+                break;
+            }
+            ancestors.push(parent.clone());
+            def = parent.get_parent();
+        }
+        if ancestors.is_empty() {
+            self.add_attribute(attr);
+            return Ok(());
+        }
+
+        let root = ancestors.pop().unwrap();
+        if self.get_attribute_by_name(root.get_name()).is_none() {
+            self.add_attribute(RadiusAttribute::new(root.clone(), Vec::new()));
+        }
+        let mut res = self.get_attribute_by_name_mut(root.get_name()).unwrap();
+        while let Some(parent) = ancestors.pop() {
+            if res.get_child_by_name(parent.get_name()).is_none() {
+                res.add_child(RadiusAttribute::new(parent.clone(), Vec::new()))?;
+            }
+            res = res.get_child_by_name_mut(parent.get_name()).unwrap();
+        }
+        res.add_child(attr)
+    }
+
+    /// retrieves a RADIUS attribute by code, Vendor-Specific and extended attributes excluded
     pub fn get_attribute_by_code(&self, code: u8) -> Option<&RadiusAttribute> {
         for i in 0..self.attributes.len() {
-            if self.attributes[i].get_definition().get_code() == code {
+            if self.attributes[i].get_definition().is_standard() && self.attributes[i].get_definition().get_code() == code {
                 return self.attributes.get(i);
             }
         }
         None
     }
 
-    /// retrieves a mutable reference to a RADIUS attribute by code
+    /// retrieves a mutable reference to a RADIUS attribute by code, Vendor-Specific and extended attributes excluded
     pub fn get_attribute_by_code_mut(&mut self, code: u8) -> Option<&mut RadiusAttribute> {
         for i in 0..self.attributes.len() {
-            if self.attributes[i].get_definition().get_code() == code {
+            if self.attributes[i].get_definition().is_standard() && self.attributes[i].get_definition().get_code() == code {
                 return self.attributes.get_mut(i);
             }
         }
@@ -96,11 +331,28 @@ impl RadiusData {
     }
 
     /// RADIUS packet byte array representation
-    pub fn get_bytes(&self) -> Vec<u8> {
+    /// fails instead of producing a malformed packet when a non concat attribute value
+    /// doesn't fit a single attribute or the packet is too long
+    pub fn get_bytes(&self) -> Result<Vec<u8>, String> {
         let mut res = Vec::new();
 
         let mut attributes = Vec::new();
-        self.attributes.iter().for_each(|a| attributes.append(&mut a.get_bytes()));
+        let mut i = 0;
+        while i < self.attributes.len() {
+            let (attr, mut packed) = RadiusAttribute::pack(&self.attributes[i..]);
+            let mut group = vec![attr];
+            // vendor attributes decoded from a single Vendor-Specific attribute are encoded back together
+            while i + packed < self.attributes.len() && group[0].shares_vsa(&self.attributes[i + packed]) {
+                let (attr, n) = RadiusAttribute::pack(&self.attributes[(i + packed)..]);
+                group.push(attr);
+                packed += n;
+            }
+            attributes.append(&mut RadiusAttribute::encode_vsa(&group)?);
+            i += packed;
+        }
+        if 20 + attributes.len() > MAX_PACKET_LENGTH {
+            return Err(format!("Packet too long: {} bytes, maximum {}", 20 + attributes.len(), MAX_PACKET_LENGTH));
+        }
 
         res.push(self.code.to_u8());
         res.push(self.identifier);
@@ -109,10 +361,20 @@ impl RadiusData {
         res.append(&mut self.authenticator.as_slice().to_vec());//clone
         res.append(&mut attributes);
 
-        res
+        Ok(res)
     }
 
     fn from_u16_to_u8(a: u16) -> [u8; 2] {
         [a as u8, (a >> 8) as u8]
     }
 }
+
+impl fmt::Display for RadiusData {
+    /// radclient style listing, one attribute per line
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for a in self.attributes.iter() {
+            writeln!(f, "{}", a)?;
+        }
+        Ok(())
+    }
+}
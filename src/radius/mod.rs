@@ -1,8 +1,21 @@
 mod code;
 mod attribute;
 mod data;
+mod vendor;
+mod value;
+#[cfg(feature = "serde")]
+mod serialize;
 
-pub use self::code::RadiusCode;
-pub use self::attribute::RadiusAttribute;
+pub use self::code::{RadiusCode, UnknownCode};
+pub use self::attribute::{RadiusAttribute, MAX_TAG};
+pub(crate) use self::attribute::{VENDOR_SPECIFIC, LONG_EXTENDED_MORE};
+pub(crate) use self::attribute::atype::MAX_VALUE_LENGTH;
 pub use self::attribute::def::RadiusAttributeDef;
-pub use self::data::RadiusData;
+pub use self::attribute::atype::RadiusAttributeType;
+pub use self::attribute::avalue::{RadiusValue, IntoRadiusValue};
+pub use self::attribute::flags::{RadiusAttributeFlags, RadiusEncryption};
+pub use self::data::{RadiusData, MAX_PACKET_LENGTH};
+pub use self::vendor::RadiusVendorDef;
+pub use self::value::RadiusValueDef;
+#[cfg(feature = "serde")]
+pub(crate) use self::serialize::{DataEntry, EntryValue, from_hex};
@@ -0,0 +1,92 @@
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use super::{RadiusAttribute, RadiusData, RadiusValue};
+
+// serialized RadiusData layout, deserialized back through a Dictionary
+#[derive(Serialize, Deserialize)]
+pub(crate) struct DataEntry {
+    pub(crate) code: u8,
+    pub(crate) identifier: u8,
+    pub(crate) authenticator: String,
+    pub(crate) attributes: Vec<AttributeEntry>,
+}
+
+// named attribute, either typed or raw (hex) when the value doesn't fit its type
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AttributeEntry {
+    pub(crate) name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tag: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) value: Option<EntryValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) hex: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum EntryValue {
+    Unsigned(u64),
+    Signed(i64),
+    Text(String),
+}
+
+pub(crate) fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..(i + 2)], 16).ok()).collect()
+}
+
+impl AttributeEntry {
+    // tlv children are listed instead of their parent
+    fn collect(attr: &RadiusAttribute, res: &mut Vec<AttributeEntry>) {
+        if !attr.get_children().is_empty() {
+            for c in attr.get_children() {
+                Self::collect(c, res);
+            }
+            return;
+        }
+
+        let value = match attr.get_typed_value() {
+            Ok(RadiusValue::U8(n)) => Some(EntryValue::Unsigned(u64::from(n))),
+            Ok(RadiusValue::U16(n)) => Some(EntryValue::Unsigned(u64::from(n))),
+            Ok(RadiusValue::U32(n)) => Some(EntryValue::Unsigned(u64::from(n))),
+            Ok(RadiusValue::U64(n)) => Some(EntryValue::Unsigned(n)),
+            Ok(RadiusValue::I32(n)) => Some(EntryValue::Signed(i64::from(n))),
+            Ok(RadiusValue::Date(time)) => time.duration_since(UNIX_EPOCH).ok().map(|d| EntryValue::Unsigned(d.as_secs())),
+            Ok(RadiusValue::String(s)) => Some(EntryValue::Text(s)),
+            Ok(RadiusValue::Bytes(_)) | Err(_) => None,
+            Ok(_) => Some(EntryValue::Text(attr.get_text_value())),
+        };
+        res.push(AttributeEntry {
+            name: attr.get_definition().get_name().to_owned(),
+            tag: attr.get_tag().filter(|_| attr.get_definition().get_flags().has_tag()),
+            hex: if value.is_none() { Some(to_hex(attr.get_value())) } else { None },
+            value,
+        });
+    }
+}
+
+impl Serialize for RadiusData {
+    /// code, identifier, hexadecimal authenticator and the list of named attributes,
+    /// see `Dictionary::deserialize_data` for the way back
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut attributes = Vec::new();
+        for a in self.get_attributes() {
+            AttributeEntry::collect(a, &mut attributes);
+        }
+        DataEntry {
+            code: self.get_code().to_u8(),
+            identifier: self.get_identifier(),
+            authenticator: to_hex(self.get_authenticator()),
+            attributes,
+        }.serialize(serializer)
+    }
+}
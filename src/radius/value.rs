@@ -0,0 +1,60 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// RADIUS named value definition, an enumeration entry of an integer attribute
+#[derive(Clone, PartialEq, Debug)]
+pub struct RadiusValueDef {
+    attribute: String,
+    name: String,
+    value: u32,
+}
+
+impl RadiusValueDef {
+    /// retrieve the name of the attribute this value belongs to
+    pub fn get_attribute(&self) -> &str {
+        &self.attribute
+    }
+
+    /// retrieve value name
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// retrieve value number
+    pub fn get_value(&self) -> u32 {
+        self.value
+    }
+}
+
+impl FromStr for RadiusValueDef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != 4 {
+            return Err(format!("Malformed value row: {}", s));
+        }
+        if parts[0] != "VALUE" {
+            return Err(format!("Not a value row: {}", s));
+        }
+
+        let value = if parts[3].starts_with("0x") || parts[3].starts_with("0X") {
+            u32::from_str_radix(&parts[3][2..], 16)
+        }
+        else {
+            parts[3].parse::<u32>()
+        };
+
+        Ok(RadiusValueDef {
+            attribute: parts[1].to_owned(),
+            name: parts[2].to_owned(),
+            value: value.map_err(|e| format!("Malformed value number: {:?}", e))?,
+        })
+    }
+}
+
+impl fmt::Display for RadiusValueDef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VALUE\t{}\t{}\t{}", self.attribute, self.name, self.value)
+    }
+}
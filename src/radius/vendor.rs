@@ -0,0 +1,161 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// RADIUS vendor definition
+#[derive(Clone, PartialEq, Debug)]
+pub struct RadiusVendorDef {
+    name: String,
+    id: u32,
+    type_size: usize,
+    length_size: usize,
+    continuation: bool,
+}
+
+impl RadiusVendorDef {
+    /// retrieve vendor name
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// retrieve vendor id (IANA Private Enterprise Number)
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    /// retrieve the number of bytes used by the vendor attribute type
+    pub fn get_type_size(&self) -> usize {
+        self.type_size
+    }
+
+    /// retrieve the number of bytes used by the vendor attribute length
+    pub fn get_length_size(&self) -> usize {
+        self.length_size
+    }
+
+    /// true if vendor attributes carry a continuation byte (WiMAX style)
+    pub fn has_continuation(&self) -> bool {
+        self.continuation
+    }
+
+    /// Vendor-Specific value (vendor id excluded) for a single vendor attribute
+    /// fails when the value doesn't fit the vendor length field
+    pub fn encode(&self, code: u8, value: &[u8]) -> Result<Vec<u8>, String> {
+        let mut res = Vec::new();
+        res.extend_from_slice(&u32::from(code).to_be_bytes()[(4 - self.type_size)..]);
+
+        let header = self.type_size + self.length_size + if self.continuation { 1 } else { 0 };
+        let len = header + value.len();
+        if self.length_size > 0 && len >> (8 * self.length_size) != 0 {
+            return Err(format!("Value too long for {} vendor attribute {}: {} bytes", self.name, code, value.len()));
+        }
+        res.extend_from_slice(&(len as u32).to_be_bytes()[(4 - self.length_size)..]);
+        if self.continuation {
+            res.push(0);
+        }
+
+        res.extend_from_slice(value);
+        Ok(res)
+    }
+
+    /// splits a Vendor-Specific value (vendor id excluded) into (code, value) pairs
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<(u8, Vec<u8>)>, String> {
+        let header = self.type_size + self.length_size + if self.continuation { 1 } else { 0 };
+
+        let mut res = Vec::new();
+        let mut i = 0;
+        while i < data.len() {
+            if i + header > data.len() {
+                return Err(format!("Truncated {} vendor attribute", self.name));
+            }
+
+            let code = Self::read_be(&data[i..(i + self.type_size)]);
+            let size = if self.length_size == 0 {
+                data.len() - i
+            }
+            else {
+                Self::read_be(&data[(i + self.type_size)..(i + self.type_size + self.length_size)]) as usize
+            };
+            if size < header || i + size > data.len() {
+                return Err(format!("Malformed {} vendor attribute length: {}", self.name, size));
+            }
+            if code > u32::from(u8::MAX) {
+                return Err(format!("Unsupported {} vendor attribute code: {}", self.name, code));
+            }
+
+            res.push((code as u8, data[(i + header)..(i + size)].to_vec()));
+            i += size;
+        }
+
+        Ok(res)
+    }
+
+    fn read_be(data: &[u8]) -> u32 {
+        data.iter().fold(0u32, |acc, b| (acc << 8) | u32::from(*b))
+    }
+
+    fn parse_format(&mut self, s: &str) -> Result<(), String> {
+        let format = s.trim_start_matches("format=");
+        let parts: Vec<&str> = format.split(',').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err(format!("Malformed vendor format: {}", s));
+        }
+
+        self.type_size = match parts[0] {
+            "1" => 1,
+            "2" => 2,
+            "4" => 4,
+            _ => return Err(format!("Unsupported vendor type size: {}", parts[0])),
+        };
+        self.length_size = match parts[1] {
+            "0" => 0,
+            "1" => 1,
+            "2" => 2,
+            _ => return Err(format!("Unsupported vendor length size: {}", parts[1])),
+        };
+        if parts.len() == 3 {
+            if parts[2] != "c" || self.type_size != 1 || self.length_size != 1 {
+                return Err(format!("Malformed vendor format: {}", s));
+            }
+            self.continuation = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for RadiusVendorDef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != 3 && parts.len() != 4 {
+            return Err(format!("Malformed vendor row: {}", s));
+        }
+        if parts[0] != "VENDOR" {
+            return Err(format!("Not a vendor row: {}", s));
+        }
+
+        let mut res = RadiusVendorDef {
+            name: parts[1].to_owned(),
+            id: parts[2].parse::<u32>().map_err(|e| format!("Malformed vendor id: {:?}", e))?,
+            type_size: 1,
+            length_size: 1,
+            continuation: false,
+        };
+        if parts.len() == 4 {
+            res.parse_format(parts[3])?;
+        }
+
+        Ok(res)
+    }
+}
+
+impl fmt::Display for RadiusVendorDef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VENDOR\t{}\t{}", self.name, self.id)?;
+        if self.type_size != 1 || self.length_size != 1 || self.continuation {
+            write!(f, "\tformat={},{}{}", self.type_size, self.length_size, if self.continuation { ",c" } else { "" })?;
+        }
+        Ok(())
+    }
+}